@@ -1,18 +1,92 @@
 extern crate alloc;
+use core::cell::RefCell;
 use alloc::string::String;
+use alloc::vec::Vec;
 use noli::net::{lookup_host, SocketAddr, TcpStream};
 use noli::print;
 use saba_core::error::Error;
-use saba_core::http::HttpResponse;
-pub struct HttpClient {}
+use saba_core::http::{dechunk_response, find_header_end, response_length, split_raw_response, Header, HttpRequest, HttpResponse, Method};
+pub struct HttpClient {
+    keep_alive: bool,
+    // keep-alive のときに使い回す接続と、その接続先の host と port
+    connection: RefCell<Option<(String, u16, TcpStream)>>,
+}
 
 impl HttpClient {
     pub fn new() -> Self {
-        Self {}
+        Self { keep_alive: false, connection: RefCell::new(None) }
     }
 
+    // true にすると Connection: keep-alive を送り、同じ host と port へのリクエストでは接続を使い回す
+    // レスポンスの終わりは Content-Length か chunked の最後の chunk で判断する
+    pub fn set_keep_alive(&mut self, keep_alive: bool) {
+        self.keep_alive = keep_alive;
+        if !keep_alive {
+            self.connection.replace(None);
+        }
+    }
+
+    // chunked で届いた body は chunk-data だけにしてから渡す
     pub fn get(&self, host: String, port: u16, path: String) -> Result<HttpResponse, Error> {
-        let ips = match lookup_host(&host) {
+        let received = dechunk_response(self.fetch(Method::Get, host, port, path)?)?;
+
+        match String::from_utf8(received) {
+            Ok(result) =>         HttpResponse::new(result),
+            Err(e) => Err(Error::Network(alloc::format!("Invalid received response: {}", e)))
+        }
+    }
+
+    // 画像など、body が UTF-8 とは限らないリソースのためのもの。body はバイト列のまま返す
+    pub fn get_bytes(&self, host: String, port: u16, path: String) -> Result<(Vec<Header>, Vec<u8>), Error> {
+        let received = dechunk_response(self.fetch(Method::Get, host, port, path)?)?;
+        split_raw_response(&received)
+    }
+
+    // HEAD のレスポンスには body がないので、status line と header だけを解釈する
+    pub fn head(&self, host: String, port: u16, path: String) -> Result<HttpResponse, Error> {
+        let received = self.fetch(Method::Head, host, port, path)?;
+
+        match String::from_utf8(received) {
+            Ok(result) => HttpResponse::new(result),
+            Err(e) => Err(Error::Network(alloc::format!("Invalid received response: {}", e)))
+        }
+    }
+
+    // リクエストを送って、受け取ったレスポンスをそのまま返す
+    // keep-alive なら前の接続を使い回してみて、だめなら新しくつなぎ直す
+    fn fetch(&self, method: Method, host: String, port: u16, path: String) -> Result<Vec<u8>, Error> {
+        let mut request = HttpRequest::new(method, host.clone(), path);
+        request.set_keep_alive(self.keep_alive);
+        let request = request.build();
+
+        if self.keep_alive {
+            let reusable = match self.connection.borrow_mut().take() {
+                Some((h, p, stream)) if h == host && p == port => Some(stream),
+                _ => None,
+            };
+            if let Some(mut stream) = reusable {
+                // サーバーが先に接続を閉じていると何も読めないので、そのときもつなぎ直す
+                if let Ok((received, reusable)) = self.exchange(&mut stream, method, &request) {
+                    if !received.is_empty() {
+                        if reusable {
+                            self.connection.replace(Some((host, port, stream)));
+                        }
+                        return Ok(received);
+                    }
+                }
+            }
+        }
+
+        let mut stream = self.connect(&host, port)?;
+        let (received, reusable) = self.exchange(&mut stream, method, &request)?;
+        if self.keep_alive && reusable {
+            self.connection.replace(Some((host, port, stream)));
+        }
+        Ok(received)
+    }
+
+    fn connect(&self, host: &str, port: u16) -> Result<TcpStream, Error> {
+        let ips = match lookup_host(host) {
             Ok(ips) => ips,
             Err(_) => return Err(Error::Network(String::from("Failed to find IP addresses"))),
         };
@@ -23,66 +97,18 @@ impl HttpClient {
 
         let socket_addr: SocketAddr = (ips[0], port).into();
 
-        let mut stream = match TcpStream::connect(socket_addr) {
-            Ok(stream) => stream,
-            Err(_) => return Err(Error::Network(String::from("Failed to connect to TCP stream"))),
-        };
-
-        // 3. Request Line | RFC 9112 - HTTP/1.1
-        // https://datatracker.ietf.org/doc/html/rfc9112#name-request-line
-        // ----- Cited From Reference -----
-        //   HTTP-message   = start-line CRLF
-        //                    *( field-line CRLF )
-        //                    CRLF
-        //                    [ message-body ]
-        // --------------------------------
-
-        // 3. Request Line | RFC 9112 - HTTP/1.1
-        // https://datatracker.ietf.org/doc/html/rfc9112#name-request-line
-        // ----- Cited From Reference -----
-        // request-line   = method SP request-target SP HTTP-version
-        // --------------------------------
-        
-        let mut request = String::from("GET /");
-        request.push_str(&path);
-        request.push_str(" HTTP/1.1\n");
-
-        // 7.2. Host and :authority | RFC 9110 - HTTP Semantics
-        // https://datatracker.ietf.org/doc/html/rfc9110#name-host-and-authority
-        // ----- Cited From Reference -----
-        // The "Host" header field in a request provides the host and port information from the target URI, enabling the origin server to distinguish among resources while servicing requests for multiple host names.¶
-        
-        // In HTTP/2 [HTTP/2] and HTTP/3 [HTTP/3], the Host header field is, in some cases, supplanted by the ":authority" pseudo-header field of a request's control data.¶
-        
-        //   Host = uri-host [ ":" port ] ; Section 4
-        // --------------------------------
-
-        request.push_str("Host: ");
-        request.push_str(&host);
-        request.push_str("\n");
-
-        // 12.5.1. Accept | RFC 9110 - HTTP Semantics
-        // https://datatracker.ietf.org/doc/html/rfc9110#name-accept
-        // ----- Cited From Reference -----
-        // The "Accept" header field can be used by user agents to specify their preferences regarding response media types. For example, Accept header fields can be used to indicate that the request is specifically limited to a small set of desired types, as in the case of a request for an in-line image.
-        // --------------------------------
-        request.push_str("Accept: text/html\n");
-
-        // 3. Request Line | RFC 9112 - HTTP/1.1
-        // https://datatracker.ietf.org/doc/html/rfc9112#name-request-line
-        // ----- Cited From Reference -----
-        // 9.6. Tear-down
-        // The "close" connection option is defined as a signal that the sender will close this connection after completion of the response. A sender SHOULD send a Connection header field (Section 7.6.1 of [HTTP]) containing the "close" connection option when it intends to close a connection. For example,¶
-        
-        // Connection: close
-        // ¶
-        // as a request header field indicates that this is the last request that the client will send on this connection, while in a response, the same field indicates that the server is going to close this connection after the response message is complete.¶
-        // --------------------------------
-        request.push_str("Connection: close\n");
-
-        // ここ削ると408が見れる。確かに RFC で指定された CRLF が存在しない形になるので
-        request.push_str("\r\n");
+        match TcpStream::connect(socket_addr) {
+            Ok(stream) => Ok(stream),
+            Err(_) => Err(Error::Network(String::from("Failed to connect to TCP stream"))),
+        }
+    }
 
+    // request を書き込んで、レスポンスを 1 つ読む
+    // HEAD のときは body が来ないので、header の終わりまで読んだらそこで打ち切る
+    // keep-alive のときはサーバーが接続を閉じないので、Content-Length の分か chunked の最後まで読んだら打ち切る
+    // どちらもないレスポンスはサーバーが接続を閉じて終わりを示すので、閉じるまで読む
+    // 返り値の bool は、レスポンスの終わりが分かっていて接続をまだ使えるかどうか
+    fn exchange(&self, stream: &mut TcpStream, method: Method, request: &str) -> Result<(Vec<u8>, bool), Error> {
         let _bytes = match stream.write(request.as_bytes()) {
             Ok(bytes) => bytes,
             Err(_) => return Err(Error::Network(String::from("Failed to send a request to TCP stream"))),
@@ -90,7 +116,8 @@ impl HttpClient {
 
         print!("write done!\n\n\n");
 
-        let mut received = alloc::vec::Vec::new();
+        let mut received = Vec::new();
+        let mut reusable = false;
 
         loop {
             let mut buf = [0u8; 4096];
@@ -102,13 +129,28 @@ impl HttpClient {
                 break;
             }
             received.extend_from_slice(&buf[..bytes_read]);
+
+            if method == Method::Head {
+                if let Some((_, body_start)) = find_header_end(&received) {
+                    received.truncate(body_start);
+                    reusable = true;
+                    break;
+                }
+            }
+
+            if self.keep_alive {
+                if let Some(length) = response_length(&received) {
+                    if received.len() >= length {
+                        received.truncate(length);
+                        reusable = true;
+                        break;
+                    }
+                }
+            }
         }
 
         print!("read done!\n\n\n");
 
-        match String::from_utf8(received) {
-            Ok(result) =>         HttpResponse::new(result),
-            Err(e) => Err(Error::Network(alloc::format!("Invalid received response: {}", e)))
-        }
+        Ok((received, reusable))
     }
 }
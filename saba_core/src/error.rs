@@ -1,9 +1,48 @@
+use core::fmt;
+
 use alloc::string::String;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
     Network(String),
     UnexpectedInput(String),
     InvalidUI(String),
+    Url(String),
     Other(String)
-}
\ No newline at end of file
+}
+
+// ユーザーに見せるためのメッセージ。Debug だと Network("...") のようになって読みにくい
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Network(message) => write!(f, "network error: {}", message),
+            Error::UnexpectedInput(message) => write!(f, "unexpected input: {}", message),
+            Error::InvalidUI(message) => write!(f, "invalid UI: {}", message),
+            Error::Url(message) => write!(f, "invalid URL: {}", message),
+            Error::Other(message) => write!(f, "error: {}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, string::ToString};
+
+    #[test]
+    fn test_display() {
+        assert_eq!("network error: a", format!("{}", Error::Network("a".to_string())));
+        assert_eq!("unexpected input: b", format!("{}", Error::UnexpectedInput("b".to_string())));
+        assert_eq!("invalid UI: c", format!("{}", Error::InvalidUI("c".to_string())));
+        assert_eq!("invalid URL: e", format!("{}", Error::Url("e".to_string())));
+        assert_eq!("error: d", format!("{}", Error::Other("d".to_string())));
+    }
+
+    #[test]
+    fn test_eq() {
+        assert_eq!(Error::Network("a".to_string()), Error::Network("a".to_string()));
+        assert_eq!(Error::Url("b".to_string()), Error::Url("b".to_string()));
+        assert_ne!(Error::Network("a".to_string()), Error::Network("b".to_string()));
+        assert_ne!(Error::Network("a".to_string()), Error::Other("a".to_string()));
+    }
+}
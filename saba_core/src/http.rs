@@ -2,6 +2,104 @@ use alloc::{string::{String, ToString}, vec::Vec};
 use super::error::Error;
 
 
+// [] 9. Methods | RFC 9110 - HTTP Semantics
+// https://datatracker.ietf.org/doc/html/rfc9110#name-methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    // [] 9.3.2. HEAD | RFC 9110 - HTTP Semantics
+    // https://datatracker.ietf.org/doc/html/rfc9110#name-head
+    // ----- Cited From Reference -----
+    // The HEAD method is identical to GET except that the server MUST NOT send content in the response.
+    // --------------------------------
+    Head,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+        }
+    }
+}
+
+// 送信するリクエストを組み立てるためのもの。ネットワークに依存しないので core に置いてテストできるようにする
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    method: Method,
+    host: String,
+    path: String,
+    keep_alive: bool,
+}
+
+impl HttpRequest {
+    // path は Url::path() と同じく先頭の / を含まない
+    pub fn new(method: Method, host: String, path: String) -> Self {
+        Self { method, host, path, keep_alive: false }
+    }
+
+    // true なら、レスポンスの後も接続を閉じないよう頼む
+    pub fn set_keep_alive(&mut self, keep_alive: bool) {
+        self.keep_alive = keep_alive;
+    }
+
+    pub fn build(&self) -> String {
+        // [] 3. Request Line | RFC 9112 - HTTP/1.1
+        // https://datatracker.ietf.org/doc/html/rfc9112#name-request-line
+        // ----- Cited From Reference -----
+        //   HTTP-message   = start-line CRLF
+        //                    *( field-line CRLF )
+        //                    CRLF
+        //                    [ message-body ]
+        // --------------------------------
+
+        // [] 3. Request Line | RFC 9112 - HTTP/1.1
+        // https://datatracker.ietf.org/doc/html/rfc9112#name-request-line
+        // ----- Cited From Reference -----
+        // request-line   = method SP request-target SP HTTP-version
+        // --------------------------------
+        let mut request = String::from(self.method.as_str());
+        request.push_str(" /");
+        request.push_str(&self.path);
+        request.push_str(" HTTP/1.1\r\n");
+
+        // [] 7.2. Host and :authority | RFC 9110 - HTTP Semantics
+        // https://datatracker.ietf.org/doc/html/rfc9110#name-host-and-authority
+        // ----- Cited From Reference -----
+        // The "Host" header field in a request provides the host and port information from the target URI, enabling the origin server to distinguish among resources while servicing requests for multiple host names.¶
+        //   Host = uri-host [ ":" port ] ; Section 4
+        // --------------------------------
+        request.push_str("Host: ");
+        request.push_str(&self.host);
+        request.push_str("\r\n");
+
+        // [] 12.5.1. Accept | RFC 9110 - HTTP Semantics
+        // https://datatracker.ietf.org/doc/html/rfc9110#name-accept
+        // ----- Cited From Reference -----
+        // The "Accept" header field can be used by user agents to specify their preferences regarding response media types. For example, Accept header fields can be used to indicate that the request is specifically limited to a small set of desired types, as in the case of a request for an in-line image.
+        // --------------------------------
+        request.push_str("Accept: text/html\r\n");
+
+        // [] 9.6. Tear-down | RFC 9112 - HTTP/1.1
+        // https://datatracker.ietf.org/doc/html/rfc9112#name-tear-down
+        // ----- Cited From Reference -----
+        // A sender SHOULD send a Connection header field (Section 7.6.1 of [HTTP]) containing the "close" connection option when it intends to close a connection.
+        // --------------------------------
+        // HTTP/1.1 では何も書かなければ persistent だが、keep-alive を明示しておく
+        if self.keep_alive {
+            request.push_str("Connection: keep-alive\r\n");
+        } else {
+            request.push_str("Connection: close\r\n");
+        }
+
+        // ここ削ると408が見れる。確かに RFC で指定された CRLF が存在しない形になるので
+        request.push_str("\r\n");
+
+        request
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HttpResponse {
     version: String,
@@ -27,29 +125,40 @@ impl HttpResponse {
         // ----- Cited From Reference -----
         // Although the line terminator for the start-line and fields is the sequence CRLF, a recipient MAY recognize a single LF as a line terminator and ignore any preceding CR.¶
         // --------------------------------        
-        // ということで、CRLF を LF に置き換えて解釈してよいから、そうする
-        let preprocessed_response = raw_response.trim_start().replace("\r\n", "\n");
+        // ということで、LF の直前にある CR は (何個続いていても) 無視して、行末を LF にそろえる
+        let preprocessed_response = strip_cr_before_lf(raw_response.trim_start());
 
-        let (status_line, remaining_lines) = match preprocessed_response.split_once("\n") {
+        let (status_line, remaining_lines) = match preprocessed_response.split_once('\n') {
             Some((s, r)) => (s, r),
             None => return Err(Error::Network(alloc::format!("invalid http response: {}", preprocessed_response))),
         };
 
-        let (headers, body) = match remaining_lines.split_once("\n\n") {
-            Some((h, b)) => {
-                let mut headers = Vec::new();
-                for header in h.split("\n") {
-                    let splitted_header: Vec<&str> = header.splitn(2, ":").collect();
-                    headers.push(
-                        Header::new(String::from(splitted_header[0].trim()), String::from(splitted_header[1].trim()))
-                    )
-                }
-                (headers, b)
-            }
-            None => (Vec::new(), remaining_lines),
+        // header が 1 行もなければ、status line の直後がそのまま空行になる
+        // 空行がないのは壊れたレスポンスだが、残りを全部 header とみなして body は空にする
+        let (headers, body) = match remaining_lines.strip_prefix('\n') {
+            Some(b) => (Vec::new(), b),
+            None => match remaining_lines.split_once("\n\n") {
+                Some((h, b)) => (parse_header_lines(h), b),
+                None => (parse_header_lines(remaining_lines), ""),
+            },
         };
 
-        let statuses: Vec<&str> = status_line.split(" ").collect();
+        // [] 6. Hooks for standards (UTF-8 decode) | Encoding Standard
+        // https://encoding.spec.whatwg.org/#utf-8-decode
+        // ----- Cited From Reference -----
+        // If buffer is 0xEF 0xBB 0xBF, then read three bytes from ioQueue. (Do nothing with those bytes.)
+        // --------------------------------
+        // 先頭の BOM が DOM のテキストに紛れ込まないよう、1 つだけ取り除いておく
+        let body = body.strip_prefix('\u{FEFF}').unwrap_or(body);
+
+        // [] 4. Status Line | RFC 9112 - HTTP/1.1
+        // https://datatracker.ietf.org/doc/html/rfc9112#name-status-line
+        // ----- Cited From Reference -----
+        //   status-line = HTTP-version SP status-code SP [ reason-phrase ]
+        //   reason-phrase  = 1*( HTAB / SP / VCHAR / obs-text )
+        // --------------------------------
+        // reason-phrase には空白が含まれうるので、status-code より後ろは全部 reason として扱う
+        let statuses: Vec<&str> = status_line.splitn(3, ' ').collect();
 
         Ok(HttpResponse { 
             version: statuses.get(0).unwrap_or(&"").to_string(),
@@ -76,6 +185,11 @@ impl HttpResponse {
         self.headers.clone()
     }
 
+    // headers() と違って clone しない。受け取った順番と大文字小文字はそのまま
+    pub fn header_iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers.iter().map(|h| (h.name.as_str(), h.value.as_str()))
+    }
+
     pub fn body(&self) -> String {
         self.body.clone()
     }
@@ -89,6 +203,278 @@ impl HttpResponse {
 
         Err(alloc::format!("failed to find {} in headers", name))
     }
+
+    // [] 5.1. Field Names | RFC 9110 - HTTP Semantics
+    // https://datatracker.ietf.org/doc/html/rfc9110#name-field-names
+    // ----- Cited From Reference -----
+    // Field names are case-insensitive and ought to be registered within the "Hypertext Transfer Protocol (HTTP) Field Name Registry"; see Section 16.3.1.
+    // --------------------------------
+    // header_value と違って、名前の大文字小文字を区別せずに探す
+    pub fn header_value_ignore_case(&self, name: &str) -> Result<String, String> {
+        for h in &self.headers {
+            if h.name.eq_ignore_ascii_case(name) {
+                return Ok(h.value.clone());
+            }
+        }
+
+        Err(alloc::format!("failed to find {} in headers", name))
+    }
+}
+
+// [] 8.4. Content-Encoding | RFC 9110 - HTTP Semantics
+// https://datatracker.ietf.org/doc/html/rfc9110#name-content-encoding
+// ----- Cited From Reference -----
+// The "Content-Encoding" header field indicates what content codings have been applied to the representation, beyond those inherent in the media type, and thus what decoding mechanisms have to be applied in order to obtain data in the media type referenced by the Content-Type header field.
+// If one or more encodings have been applied to a representation, the sender that applied the encodings MUST generate a Content-Encoding header field that lists the content codings in the order in which they were applied.
+// --------------------------------
+// 圧縮された body は UTF-8 とは限らないので、split_raw_response で取り出したバイト列のまま受け取る
+// 展開のアルゴリズムは no_std の core には持たせず、呼び出し側から decoder として渡してもらう
+// 最後にかけられた coding が gzip / deflate のときだけ展開し、それ以外 (Content-Encoding がない場合を含む) は body をそのまま返す
+pub fn decode_body(headers: &[Header], body: &[u8], decoder: &dyn ContentDecoder) -> Result<Vec<u8>, Error> {
+    let last_coding = headers
+        .iter()
+        .rev()
+        .find(|h| h.name.eq_ignore_ascii_case("Content-Encoding"))
+        .and_then(|h| h.value.rsplit(',').next())
+        .map(|coding| coding.trim().to_ascii_lowercase());
+
+    match last_coding.as_deref() {
+        Some("gzip") | Some("x-gzip") | Some("deflate") => decoder.decode(body),
+        _ => Ok(body.to_vec()),
+    }
+}
+
+// 圧縮された body を展開するもの。gzip などの実装は core の外に置く
+pub trait ContentDecoder {
+    fn decode(&self, encoded: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+// status line を除いた、LF 区切りの header 部分を Header の列にする
+// : を含まない行 (空行を含む) は header として読めないので捨てる
+fn parse_header_lines(h: &str) -> Vec<Header> {
+    let mut headers = Vec::new();
+    for header in h.split('\n') {
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push(Header::new(String::from(name.trim()), String::from(value.trim())))
+        }
+    }
+    headers
+}
+
+// header の終わりの空行 (LF の後に CR が 0 個以上続いて LF) を探す
+// 見つかったら、header の最後の LF の位置と body の開始位置を返す
+pub fn find_header_end(raw: &[u8]) -> Option<(usize, usize)> {
+    for (i, b) in raw.iter().enumerate() {
+        if *b != b'\n' {
+            continue;
+        }
+        let mut j = i + 1;
+        while j < raw.len() && raw[j] == b'\r' {
+            j += 1;
+        }
+        if j < raw.len() && raw[j] == b'\n' {
+            return Some((i, j + 1));
+        }
+    }
+    None
+}
+
+// [] 6.3. Message Body Length | RFC 9112 - HTTP/1.1
+// https://datatracker.ietf.org/doc/html/rfc9112#name-message-body-length
+// ----- Cited From Reference -----
+// Any response to a HEAD request and any response with a 1xx (Informational), 204 (No Content), or 304 (Not Modified) status code is always terminated by the first empty line after the header fields, regardless of the header fields present in the message, and thus cannot contain a message body or trailer section.
+// If a valid Content-Length header field is present without Transfer-Encoding, its decimal value defines the expected message body length in octets.
+// If a Transfer-Encoding header field is present and the chunked transfer coding (Section 7.1) is the final encoding, the message body length is determined by reading and decoding the chunked data until the transfer coding indicates the data is complete.
+// --------------------------------
+// 接続を使い回すときに、どこまで読めばレスポンスが終わるかを求める
+// Content-Length があれば body が途中まででも全体の長さが分かる。chunked なら最後の chunk まで届いてはじめて長さが分かる
+// body を持たない status なら header の終わりまで。HEAD のレスポンスは呼び出し側で header の終わりまでとする
+// header の終わりまで届いていないときや、どちらもなくて接続が閉じるまで読むしかないときは None
+pub fn response_length(raw: &[u8]) -> Option<usize> {
+    let (head_end, body_start) = find_header_end(raw)?;
+    let head = strip_cr_before_lf(core::str::from_utf8(&raw[..head_end]).ok()?);
+    // header が 1 行もなければ head は status line だけになる。最後の LF は head に含まれないので CR が残りうる
+    let (status_line, headers) = match head.split_once('\n') {
+        Some((status_line, headers)) => (status_line, parse_header_lines(headers)),
+        None => (head.trim_end_matches('\r'), Vec::new()),
+    };
+
+    let status_code = status_line.split(' ').nth(1).and_then(|code| code.parse::<u32>().ok());
+    if matches!(status_code, Some(100..=199) | Some(204) | Some(304)) {
+        return Some(body_start);
+    }
+
+    if is_chunked(&headers) {
+        return chunked_body_length(&raw[body_start..]).map(|length| body_start + length);
+    }
+
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|h| h.value.parse::<usize>().ok())
+        .map(|length| body_start + length)
+}
+
+// Transfer-Encoding の最後の coding が chunked か
+fn is_chunked(headers: &[Header]) -> bool {
+    headers
+        .iter()
+        .rev()
+        .find(|h| h.name.eq_ignore_ascii_case("Transfer-Encoding"))
+        .and_then(|h| h.value.rsplit(',').next())
+        .map(|coding| coding.trim().eq_ignore_ascii_case("chunked"))
+        .unwrap_or(false)
+}
+
+// [] 7.1. Chunked Transfer Coding | RFC 9112 - HTTP/1.1
+// https://datatracker.ietf.org/doc/html/rfc9112#name-chunked-transfer-coding
+// ----- Cited From Reference -----
+//   chunked-body   = *chunk
+//                    last-chunk
+//                    trailer-section
+//                    CRLF
+//   chunk          = chunk-size [ chunk-ext ] CRLF
+//                    chunk-data CRLF
+//   last-chunk     = 1*("0") [ chunk-ext ] CRLF
+// --------------------------------
+// chunked な body の、trailer の後ろの空行までを含めた長さ。最後まで届いていないか、chunk-size が読めなければ None
+fn chunked_body_length(body: &[u8]) -> Option<usize> {
+    parse_chunked(body).map(|(_, length)| length)
+}
+
+// chunked な body から chunk-data だけをつなげて取り出す。chunk-ext と trailer は捨てる
+// 最後の chunk まで届いていないか、chunk-size が読めなければ None
+pub fn dechunk(body: &[u8]) -> Option<Vec<u8>> {
+    parse_chunked(body).map(|(data, _)| data)
+}
+
+// chunked な body を読んで、つなげた chunk-data と、trailer の後ろの空行までを含めた長さを返す
+// 行末は他と同じく LF を区切りとし、直前の CR は無視する
+fn parse_chunked(body: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let next_line = |pos: usize| -> Option<(&[u8], usize)> {
+        let lf = pos + body.get(pos..)?.iter().position(|b| *b == b'\n')?;
+        let mut line = &body[pos..lf];
+        while let [rest @ .., b'\r'] = line {
+            line = rest;
+        }
+        Some((line, lf + 1))
+    };
+
+    let mut data = Vec::new();
+    let mut pos = 0;
+    loop {
+        let (line, next) = next_line(pos)?;
+        let size = core::str::from_utf8(line).ok()?.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        pos = next;
+
+        if size == 0 {
+            // trailer の行を読み飛ばして、空行で終わる
+            loop {
+                let (line, next) = next_line(pos)?;
+                pos = next;
+                if line.is_empty() {
+                    return Some((data, pos));
+                }
+            }
+        }
+
+        // chunk-data の後ろの CRLF
+        let end = pos.checked_add(size)?;
+        let (_, next) = next_line(end)?;
+        data.extend_from_slice(&body[pos..end]);
+        pos = next;
+    }
+}
+
+// chunked で届いたレスポンスの body を chunk-data だけにして、status line と header はそのまま返す
+// chunked でなければ何もしない。最後の chunk まで届いていなければ Err
+pub fn dechunk_response(raw: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let (head_end, body_start) = match find_header_end(&raw) {
+        Some(b) => b,
+        None => return Ok(raw),
+    };
+    let head = match core::str::from_utf8(&raw[..head_end]) {
+        Ok(h) => strip_cr_before_lf(h),
+        Err(_) => return Ok(raw),
+    };
+    let headers = match head.split_once('\n') {
+        Some((_status_line, h)) => parse_header_lines(h),
+        None => Vec::new(),
+    };
+    if !is_chunked(&headers) {
+        return Ok(raw);
+    }
+
+    match dechunk(&raw[body_start..]) {
+        Some(body) => {
+            let mut result = raw[..body_start].to_vec();
+            result.extend_from_slice(&body);
+            Ok(result)
+        }
+        None => Err(Error::Network(String::from("invalid chunked body"))),
+    }
+}
+
+// 画像などのために、body を UTF-8 として解釈せずにレスポンスを header と body に分ける
+// status line と header は ASCII のはずなので文字列として読み、body はバイト列のまま返す
+// 行末の扱いは HttpResponse::new と同じで、LF の直前の CR は無視する
+pub fn split_raw_response(raw: &[u8]) -> Result<(Vec<Header>, Vec<u8>), Error> {
+    let start = raw.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(raw.len());
+    let raw = &raw[start..];
+
+    let boundary = find_header_end(raw);
+
+    let (head_end, body_start) = match boundary {
+        Some(b) => b,
+        // 空行がなければ、HttpResponse::new と同様に status line の後ろを丸ごと header とし、body は空にする
+        None => match raw.iter().position(|b| *b == b'\n') {
+            Some(_) => (raw.len(), raw.len()),
+            None => {
+                return Err(Error::Network(alloc::format!(
+                    "invalid http response: {}",
+                    String::from_utf8_lossy(raw)
+                )))
+            }
+        },
+    };
+
+    let head = match core::str::from_utf8(&raw[..head_end]) {
+        Ok(h) => strip_cr_before_lf(h),
+        Err(e) => return Err(Error::Network(alloc::format!("invalid http response header: {}", e))),
+    };
+    let headers = match head.split_once('\n') {
+        Some((_status_line, h)) => parse_header_lines(h),
+        None => Vec::new(),
+    };
+
+    Ok((headers, raw[body_start..].to_vec()))
+}
+
+// LF の直前に続く CR をすべて取り除く。LF が後ろに来ない CR はそのまま残す
+fn strip_cr_before_lf(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut pending_cr = 0;
+    for c in s.chars() {
+        match c {
+            '\r' => pending_cr += 1,
+            '\n' => {
+                pending_cr = 0;
+                result.push('\n');
+            }
+            _ => {
+                for _ in 0..pending_cr {
+                    result.push('\r');
+                }
+                pending_cr = 0;
+                result.push(c);
+            }
+        }
+    }
+    for _ in 0..pending_cr {
+        result.push('\r');
+    }
+    result
 }
 
 #[derive(Debug, Clone)]
@@ -101,12 +487,138 @@ impl Header {
     fn new(name: String, value: String) -> Self {
         Self { name, value }
     }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn value(&self) -> String {
+        self.value.clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_build_get_request() {
+        let request = HttpRequest::new(Method::Get, "example.com".to_string(), "index.html".to_string()).build();
+        assert_eq!(
+            "GET /index.html HTTP/1.1\r\nHost: example.com\r\nAccept: text/html\r\nConnection: close\r\n\r\n",
+            request
+        );
+    }
+
+    #[test]
+    fn test_build_request_crlf() {
+        let request = HttpRequest::new(Method::Get, "example.com".to_string(), "".to_string()).build();
+        assert!(request.ends_with("\r\n\r\n"));
+        // LF はすべて CR の直後にある
+        for (i, b) in request.bytes().enumerate() {
+            if b == b'\n' {
+                assert_eq!(b'\r', request.as_bytes()[i - 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_head_request() {
+        let request = HttpRequest::new(Method::Head, "example.com".to_string(), "index.html".to_string()).build();
+        assert_eq!(Some("HEAD /index.html HTTP/1.1"), request.lines().next());
+    }
+
+    #[test]
+    fn test_build_keep_alive_request() {
+        let mut request = HttpRequest::new(Method::Get, "example.com".to_string(), "index.html".to_string());
+        request.set_keep_alive(true);
+        let request = request.build();
+
+        assert!(request.contains("\r\nConnection: keep-alive\r\n"));
+        assert!(!request.contains("close"));
+        assert!(request.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_response_length() {
+        let raw = b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\n\r\nbody";
+        assert_eq!(Some(raw.len()), response_length(raw));
+        // 途中までしか届いていなくても、header が揃っていれば全体の長さは分かる
+        assert_eq!(Some(raw.len()), response_length(&raw[..raw.len() - 2]));
+
+        assert_eq!(None, response_length(b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\n"));
+        assert_eq!(None, response_length(b"HTTP/1.1 200 OK\r\nDate: xx\r\n\r\nbody"));
+    }
+
+    #[test]
+    fn test_response_length_bodiless() {
+        // body を持たない status は Content-Length がなくても header の終わりで終わる
+        for raw in [
+            &b"HTTP/1.1 204 No Content\r\n\r\n"[..],
+            b"HTTP/1.1 304\r\n\r\n",
+            b"HTTP/1.1 304 Not Modified\r\nETag: x\r\n\r\n",
+            b"HTTP/1.1 100 Continue\r\n\r\n",
+        ] {
+            assert_eq!(Some(raw.len()), response_length(raw));
+        }
+
+        // header が 1 行もなくても header の終わりは分かる。Content-Length がなければ接続が閉じるまで読む
+        assert_eq!(None, response_length(b"HTTP/1.1 200 OK\r\n\r\nbody"));
+        assert_eq!(Some(19), response_length(b"HTTP/1.1 204 OK\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_response_length_chunked() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nbody\r\na;ext=1\r\n0123456789\r\n0\r\n\r\n";
+        assert_eq!(Some(raw.len()), response_length(raw));
+        // 最後の chunk と空行が届くまでは終わりが分からない
+        assert_eq!(None, response_length(&raw[..raw.len() - 2]));
+        assert_eq!(None, response_length(&raw[..raw.len() - 5]));
+
+        // trailer があってもその後ろの空行で終わる。Content-Length より Transfer-Encoding を優先する
+        let raw = b"HTTP/1.1 200 OK\r\ncontent-length: 1\r\ntransfer-encoding: gzip, chunked\r\n\r\n0\r\nExpires: xx\r\n\r\n";
+        assert_eq!(Some(raw.len()), response_length(raw));
+    }
+
+    #[test]
+    fn test_dechunk() {
+        let body = b"4\r\nbody\r\na;ext=1\r\n0123456789\r\n0\r\nExpires: xx\r\n\r\n";
+        assert_eq!(Some(b"body0123456789".to_vec()), dechunk(body));
+        // 最後の chunk が届いていなければ取り出せない
+        assert_eq!(None, dechunk(&body[..10]));
+
+        // chunk-data の中の CRLF はそのまま残す
+        assert_eq!(Some(b"a\r\nb".to_vec()), dechunk(b"4\r\na\r\nb\r\n0\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_dechunk_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n<p>\r\n4\r\n</p>\r\n0\r\n\r\n".to_vec();
+        let res = dechunk_response(raw).expect("failed to dechunk");
+        assert_eq!(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n<p></p>".to_vec(), res);
+
+        let raw = String::from_utf8(res).expect("invalid utf-8");
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.body(), "<p></p>");
+
+        // chunked でなければそのまま
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n0\r\n".to_vec();
+        assert_eq!(Ok(raw.clone()), dechunk_response(raw));
+
+        // 途中で切れていたら Err
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n<p>".to_vec();
+        assert!(dechunk_response(raw).is_err());
+    }
+
+    #[test]
+    fn test_find_header_end() {
+        let raw = b"HTTP/1.1 200 OK\r\nDate: xx\r\n\r\nbody";
+        assert_eq!(Some((26, 29)), find_header_end(raw));
+        assert_eq!(b"body", &raw[29..]);
+
+        assert_eq!(None, find_header_end(b"HTTP/1.1 200 OK\r\nDate: xx\r\n"));
+    }
+
     #[test]
     fn test_invalid() {
         let raw = "HTTP/1.1 200 OK".to_string();
@@ -122,6 +634,24 @@ mod tests {
         assert_eq!(res.reason(), "OK");
     }
 
+    #[test]
+    fn test_multi_word_reason() {
+        let raw = "HTTP/1.1 404 Not Found\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.version(), "HTTP/1.1");
+        assert_eq!(res.status_code(), 404);
+        assert_eq!(res.reason(), "Not Found");
+    }
+
+    #[test]
+    fn test_empty_reason() {
+        let raw = "HTTP/1.1 204 \n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.version(), "HTTP/1.1");
+        assert_eq!(res.status_code(), 204);
+        assert_eq!(res.reason(), "");
+    }
+
     #[test]
     fn test_one_header() {
         let raw = "HTTP/1.1 200 OK\nDate:xx xx xx\n\n".to_string();
@@ -145,6 +675,40 @@ mod tests {
         assert_eq!(res.header_value("Content-Length"), Ok("42".to_string()));
     }
 
+    #[test]
+    fn test_header_iter() {
+        let raw = "HTTP/1.1 200 OK\nDate: xx xx xx\ncontent-length: 42\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+
+        let mut iter = res.header_iter();
+        assert_eq!(Some(("Date", "xx xx xx")), iter.next());
+        assert_eq!(Some(("content-length", "42")), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn test_no_blank_line() {
+        let raw = "HTTP/1.1 200 OK\nDate: xx xx xx\nbroken header\nContent-Length: 0".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.status_code(), 200);
+
+        // 空行がなければ残りはすべて header。: のない行は無視する
+        assert_eq!(res.header_value("Date"), Ok("xx xx xx".to_string()));
+        assert_eq!(res.header_value("Content-Length"), Ok("0".to_string()));
+        assert_eq!(res.headers().len(), 2);
+        assert_eq!(res.body(), "".to_string());
+    }
+
+    #[test]
+    fn test_no_headers() {
+        for raw in ["HTTP/1.1 200 OK\n\nbody", "HTTP/1.1 200 OK\r\n\r\nbody"] {
+            let res = HttpResponse::new(raw.to_string()).expect("failed to parse http response");
+            assert_eq!(res.status_code(), 200);
+            assert!(res.headers().is_empty());
+            assert_eq!(res.body(), "body".to_string());
+        }
+    }
+
     #[test]
     fn test_body() {
         let raw = "HTTP/1.1 200 OK\nDate: xx xx xx\n\nbody message".to_string();
@@ -158,6 +722,15 @@ mod tests {
         assert_eq!(res.body(), "body message".to_string());
     }
 
+    #[test]
+    fn test_body_with_bom() {
+        let raw = "HTTP/1.1 200 OK\nContent-Type: text/html\n\n\u{FEFF}\u{FEFF}<html></html>".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+
+        // 取り除くのは先頭の 1 つだけ
+        assert_eq!(res.body(), "\u{FEFF}<html></html>".to_string());
+    }
+
     #[test]
     fn test_crlf() {
         let raw = "HTTP/1.1 200 OK\r\nDate: xx xx xx\r\n\r\nbody message".to_string();
@@ -170,4 +743,102 @@ mod tests {
 
         assert_eq!(res.body(), "body message".to_string());
     }
+
+    #[test]
+    fn test_cr_before_lf() {
+        let raw = "HTTP/1.1 200 OK\r\r\nDate: xx xx xx\r\r\nContent-Length: 5\r\n\r\r\na\rb\r\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(res.version(), "HTTP/1.1");
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.reason(), "OK");
+
+        assert_eq!(res.header_value("Date"), Ok("xx xx xx".to_string()));
+        assert_eq!(res.header_value("Content-Length"), Ok("5".to_string()));
+
+        // LF が続かない CR は残る
+        assert_eq!(res.body(), "a\rb\n".to_string());
+    }
+
+    // テスト用に、バイト列を逆順にするだけの decoder
+    struct ReverseDecoder;
+
+    impl ContentDecoder for ReverseDecoder {
+        fn decode(&self, encoded: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(encoded.iter().rev().copied().collect())
+        }
+    }
+
+    #[test]
+    fn test_decode_body_gzip() {
+        // gzip の body は UTF-8 として読めないことがあるので、バイト列のまま扱う
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0x1f, 0x8b, 0xff]);
+        let (headers, body) = split_raw_response(&raw).expect("failed to split http response");
+        assert_eq!(Ok([0xff, 0x8b, 0x1f].to_vec()), decode_body(&headers, &body, &ReverseDecoder));
+    }
+
+    #[test]
+    fn test_decode_body_lowercase_header() {
+        let (headers, body) = split_raw_response(b"HTTP/1.1 200 OK\ncontent-encoding: GZIP\n\nabc").expect("failed to split http response");
+        assert_eq!(Ok(b"cba".to_vec()), decode_body(&headers, &body, &ReverseDecoder));
+    }
+
+    #[test]
+    fn test_decode_body_multiple_codings() {
+        // 最後にかけられた coding だけを見る
+        let (headers, body) = split_raw_response(b"HTTP/1.1 200 OK\nContent-Encoding: identity, gzip\n\nabc").expect("failed to split http response");
+        assert_eq!(Ok(b"cba".to_vec()), decode_body(&headers, &body, &ReverseDecoder));
+
+        let (headers, body) = split_raw_response(b"HTTP/1.1 200 OK\nContent-Encoding: gzip, br\n\nabc").expect("failed to split http response");
+        assert_eq!(Ok(b"abc".to_vec()), decode_body(&headers, &body, &ReverseDecoder));
+    }
+
+    #[test]
+    fn test_header_value_ignore_case() {
+        let raw = "HTTP/1.1 200 OK\nContent-type: text/html\n\n".to_string();
+        let res = HttpResponse::new(raw).expect("failed to parse http response");
+        assert_eq!(Ok("text/html".to_string()), res.header_value_ignore_case("content-type"));
+        assert_eq!(Ok("text/html".to_string()), res.header_value_ignore_case("CONTENT-TYPE"));
+        assert!(res.header_value("Content-Type").is_err());
+        assert!(res.header_value_ignore_case("Content-Length").is_err());
+    }
+
+    #[test]
+    fn test_decode_body_identity() {
+        let (headers, body) = split_raw_response(b"HTTP/1.1 200 OK\nContent-Type: text/html\n\nabc").expect("failed to split http response");
+        assert_eq!(Ok(b"abc".to_vec()), decode_body(&headers, &body, &ReverseDecoder));
+    }
+
+    #[test]
+    fn test_split_raw_response() {
+        let mut raw = b"HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: 4\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0x89, 0xff, 0x00, 0x0a]);
+        let (headers, body) = split_raw_response(&raw).expect("failed to split http response");
+        assert_eq!(2, headers.len());
+        assert_eq!("Content-Type", headers[0].name());
+        assert_eq!("image/png", headers[0].value());
+        assert_eq!("Content-Length", headers[1].name());
+        assert_eq!("4", headers[1].value());
+        assert_eq!(&[0x89, 0xff, 0x00, 0x0a], body.as_slice());
+    }
+
+    #[test]
+    fn test_split_raw_response_status_line_only() {
+        let (headers, body) = split_raw_response(b"HTTP/1.1 204 No Content\n\n").expect("failed to split http response");
+        assert!(headers.is_empty());
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_split_raw_response_no_blank_line() {
+        let (headers, body) = split_raw_response(b"HTTP/1.1 200 OK\r\nDate: xx\r\nbroken").expect("failed to split http response");
+        assert_eq!(1, headers.len());
+        assert_eq!("Date", headers[0].name());
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_split_raw_response_invalid() {
+        assert!(split_raw_response(b"HTTP/1.1 200 OK").is_err());
+    }
 }
@@ -1,3 +1,4 @@
 pub mod html;
 pub mod dom;
 pub mod css;
+pub mod layout;
@@ -0,0 +1,88 @@
+use alloc::vec::Vec;
+
+use super::token::CssToken;
+
+// [] 4. Representing Colors: the <color> type | CSS Color Module Level 4
+// https://www.w3.org/TR/css-color-4/#color-type
+// アルファは今のところ扱わない
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn black() -> Self {
+        Self::new(0, 0, 0)
+    }
+
+    // 色のキーワードか #rgb / #rrggbb を解釈する。解釈できなければ None
+    pub fn from_css(token: &CssToken) -> Option<Self> {
+        match token {
+            CssToken::Ident(name) => Self::from_name(name),
+            CssToken::HashToken(hash) => Self::from_hex(hash.strip_prefix('#').unwrap_or(hash)),
+            _ => None,
+        }
+    }
+
+    // [] 6.1 Named Colors | CSS Color Module Level 4
+    // https://www.w3.org/TR/css-color-4/#named-colors
+    // とりあえずよく使うものだけ
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "black" => Some(Self::new(0, 0, 0)),
+            "white" => Some(Self::new(255, 255, 255)),
+            "red" => Some(Self::new(255, 0, 0)),
+            "green" => Some(Self::new(0, 128, 0)),
+            "blue" => Some(Self::new(0, 0, 255)),
+            _ => None,
+        }
+    }
+
+    // [] 5.2 The RGB hexadecimal notations | CSS Color Module Level 4
+    // https://www.w3.org/TR/css-color-4/#hex-notation
+    // ----- Cited From Reference -----
+    // The three-digit RGB notation (#RGB) is converted into six-digit form (#RRGGBB) by replicating digits, not by adding zeros.
+    // --------------------------------
+    fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<Vec<u8>>>()?;
+
+        match digits.as_slice() {
+            [r, g, b] => Some(Self::new(r * 17, g * 17, b * 17)),
+            [r1, r2, g1, g2, b1, b2] => Some(Self::new(r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_named_color() {
+        assert_eq!(Some(Color::new(255, 0, 0)), Color::from_css(&CssToken::Ident("red".to_string())));
+        assert_eq!(Some(Color::new(0, 128, 0)), Color::from_css(&CssToken::Ident("green".to_string())));
+        assert_eq!(None, Color::from_css(&CssToken::Ident("reddish".to_string())));
+    }
+
+    #[test]
+    fn test_hex_color() {
+        assert_eq!(Some(Color::new(255, 255, 255)), Color::from_css(&CssToken::HashToken("#fff".to_string())));
+        assert_eq!(Some(Color::new(0, 255, 0)), Color::from_css(&CssToken::HashToken("#00ff00".to_string())));
+        assert_eq!(Some(Color::new(0x12, 0x34, 0xab)), Color::from_css(&CssToken::HashToken("#1234AB".to_string())));
+    }
+
+    #[test]
+    fn test_invalid_color() {
+        assert_eq!(None, Color::from_css(&CssToken::HashToken("#xyz".to_string())));
+        assert_eq!(None, Color::from_css(&CssToken::HashToken("#ffff".to_string())));
+        assert_eq!(None, Color::from_css(&CssToken::Number(255.0)));
+    }
+}
@@ -1,6 +1,6 @@
 use core::iter::Peekable;
 
-use alloc::{string::{String, ToString}, vec::Vec};
+use alloc::{string::{String, ToString}, vec, vec::Vec};
 
 use super::token::{CssToken, CssTokenizer};
 
@@ -16,30 +16,127 @@ impl CssParser {
 
     pub fn parse_stylesheet(&mut self) -> StyleSheet {
         let mut sheet = StyleSheet::new();
-        sheet.set_rules(self.consume_list_of_rules());
+        let (rules, at_rules) = self.consume_list_of_rules(true);
+        sheet.set_rules(rules);
+        sheet.set_at_rules(at_rules);
         sheet
     }
 
-    fn consume_list_of_rules(&mut self) -> Vec<QualifiedRule> {
+    // [] 5.3.7. Parse a list of declarations | CSS Syntax Module Level 3
+    // https://www.w3.org/TR/css-syntax-3/#parse-list-of-declarations
+    // style 属性の値のように、{} で囲まれていない declaration の並びを読む。壊れた declaration は読み飛ばす
+    pub fn parse_declarations(&mut self) -> Vec<Declaration> {
+        self.consume_list_of_declarations()
+    }
+
+    // querySelector などに渡されたセレクタの文字列を読む。今のところ単純セレクタ 1 つだけをサポートする
+    // スタイルシートのセレクタと同じ consume_complex_selector で読み、結合子を含むものは受け付けない
+    // 読めないもの、余計なトークンが残っているものは None を返す
+    pub fn parse_selector(&mut self) -> Option<Selector> {
+        self.skip_whitespace();
+        let selector = match self.consume_complex_selector() {
+            Selector::UnknownSelector | Selector::ComplexSelector(_) => return None,
+            selector => selector,
+        };
+
+        self.skip_whitespace();
+        if self.tokenizer.peek().is_some() {
+            return None;
+        }
+        Some(selector)
+    }
+
+    // [] 5.4.1 Consume a list of rules | CSS Syntax Module Level 3
+    // https://www.w3.org/TR/css-syntax-3/#consume-list-of-rules
+    // top_level でない、つまり @media などのブロックの中身を読んでいるときは、閉じ括弧でリストが終わる
+    fn consume_list_of_rules(&mut self, top_level: bool) -> (Vec<QualifiedRule>, Vec<AtRule>) {
         let mut rules = Vec::new();
+        let mut at_rules = Vec::new();
 
         loop {
             let token = match self.tokenizer.peek() {
                 Some(t) => t,
-                None => return rules,
+                None => return (rules, at_rules),
             };
 
             match token {
+                CssToken::Whitespace => {
+                    self.tokenizer.next();
+                }
+                CssToken::CloseCurly if !top_level => {
+                    assert_eq!(self.tokenizer.next(), Some(CssToken::CloseCurly));
+                    return (rules, at_rules);
+                }
                 CssToken::AtKeyword(_keyword) => {
-                    let _rule = self.consume_qualified_rule();
+                    let rule = self.consume_at_rule();
+                    match rule {
+                        Some(r) => at_rules.push(r),
+                        None => return (rules, at_rules),
+                    }
                 }
                 _ => {
                     let rule = self.consume_qualified_rule();
                     match rule {
                         Some(r) => rules.push(r),
-                        None => return rules,
+                        None => return (rules, at_rules),
+                    }
+                }
+            }
+        }
+    }
+
+    // [] 5.4.2 Consume an at-rule | CSS Syntax Module Level 3
+    // https://www.w3.org/TR/css-syntax-3/#consume-at-rule
+    // ブロックの中身をルールのリストとして読むのは @media と @supports だけ。それ以外のブロックは中身を読み捨てる
+    // at-rule の中の at-rule は今のところ保持しない
+    fn consume_at_rule(&mut self) -> Option<AtRule> {
+        let name = match self.tokenizer.next() {
+            Some(CssToken::AtKeyword(name)) => name,
+            _ => return None,
+        };
+        let mut rule = AtRule::new(name);
+
+        loop {
+            let token = match self.tokenizer.next() {
+                Some(t) => t,
+                None => return Some(rule),
+            };
+
+            match token {
+                CssToken::SemiColon => return Some(rule),
+                CssToken::OpenCurly => {
+                    if rule.name == "media" || rule.name == "supports" {
+                        let (rules, _at_rules) = self.consume_list_of_rules(false);
+                        rule.set_rules(rules);
+                    } else {
+                        self.skip_block();
                     }
+                    return Some(rule);
                 }
+                _ => rule.prelude.push(token),
+            }
+
+            // prelude の前後の空白は意味がないので落としておく
+            if rule.prelude.first() == Some(&CssToken::Whitespace) {
+                rule.prelude.remove(0);
+            }
+            if matches!(self.tokenizer.peek(), Some(CssToken::OpenCurly) | Some(CssToken::SemiColon))
+                && rule.prelude.last() == Some(&CssToken::Whitespace)
+            {
+                rule.prelude.pop();
+            }
+        }
+    }
+
+    // { の直後から対応する } までを読み捨てる
+    fn skip_block(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.tokenizer.next() {
+                Some(CssToken::OpenCurly) => depth += 1,
+                Some(CssToken::CloseCurly) => depth -= 1,
+                Some(_) => {}
+                None => return,
             }
         }
     }
@@ -59,52 +156,159 @@ impl CssParser {
                     rule.set_declarations(self.consume_list_of_declarations());
                     return Some(rule);
                 }
+                CssToken::Whitespace => {
+                    // セレクタの前の空白は意味がないので読み捨てる。セレクタの間の空白は consume_complex_selector で扱う
+                    self.tokenizer.next();
+                }
+                CssToken::Delim(',') => {
+                    // h1, p { ... } のようなセレクタのリスト。宣言はリスト中のすべてのセレクタで共有する
+                    assert_eq!(self.tokenizer.next(), Some(CssToken::Delim(',')));
+                }
                 _ => {
-                    rule.set_selector(self.consume_selector());
+                    rule.selectors.push(self.consume_complex_selector());
+                }
+            }
+        }
+    }
+
+    // [] 16. Combinators | Selectors Level 4
+    // https://www.w3.org/TR/selectors-4/#combinators
+    // div p や ul > li のように、セレクタを結合子でつないだものを読む。つながっていなければ単純なセレクタをそのまま返す
+    // 兄弟結合子 (+ と ~) や div.a のような複合セレクタはまだサポートしていないので、そのセレクタは UnknownSelector にする
+    // 一部だけ読んで広くマッチするセレクタにしてしまうと、本来当たらない要素にまで宣言が効いてしまう
+    fn consume_complex_selector(&mut self) -> Selector {
+        let first = self.consume_selector();
+        if first == Selector::UnknownSelector {
+            return self.skip_selector();
+        }
+        let mut selector = ComplexSelector::new(first);
+
+        loop {
+            let mut combinator = None;
+            while self.tokenizer.peek() == Some(&CssToken::Whitespace) {
+                self.tokenizer.next();
+                combinator = Some(Combinator::Descendant);
+            }
+
+            match self.tokenizer.peek() {
+                Some(CssToken::OpenCurly) | Some(CssToken::Delim(',')) | None => break,
+                Some(CssToken::Delim('>')) => {
+                    self.tokenizer.next();
+                    self.skip_whitespace();
+                    combinator = Some(Combinator::Child);
                 }
+                Some(CssToken::Delim('+')) | Some(CssToken::Delim('~')) => return self.skip_selector(),
+                _ => {}
             }
+
+            // 結合子を挟まずに次の単純セレクタが続くなら div.a のような複合セレクタ
+            let combinator = match combinator {
+                Some(c) => c,
+                None => return self.skip_selector(),
+            };
+            // p > { ... } のように結合子の後ろにセレクタがなければ、セレクタ全体が読めない
+            let part = self.consume_selector();
+            if part == Selector::UnknownSelector {
+                return self.skip_selector();
+            }
+            selector.push(combinator, part);
+        }
+
+        if selector.parts.len() == 1 {
+            return selector.parts.remove(0);
         }
+        Selector::ComplexSelector(selector)
     }
 
+    // 読めないセレクタは UnknownSelector を返す。宣言ブロックの { とセレクタリストの , は読まずに残す
     fn consume_selector(&mut self) -> Selector {
-        let token = match self.tokenizer.next() {
-            Some(t) => t,
-            None => panic!("should have a token but got None"),
+        let token = match self.tokenizer.peek() {
+            Some(CssToken::OpenCurly) | Some(CssToken::Delim(',')) | None => return Selector::UnknownSelector,
+            Some(_) => self.tokenizer.next().expect("should have a token after peek"),
         };
 
         match token {
-            CssToken::HashToken(value) => Selector::IdSelector(value[1..].to_string()),
+            // # だけで名前が続かないものは ID セレクタにならない
+            CssToken::HashToken(value) if value.len() > 1 => Selector::IdSelector(value[1..].to_string()),
             CssToken::Delim(delim) => {
                 if delim == '.' {
-                    return Selector::ClassSelector(self.consume_ident());
+                    return match self.consume_ident() {
+                        Some(class) => Selector::ClassSelector(class),
+                        None => Selector::UnknownSelector,
+                    };
                 }
-                panic!("Parse error: {:?} is an unexpected token.", token);
+                if delim == '*' {
+                    return Selector::UniversalSelector;
+                }
+                Selector::UnknownSelector
             },
             CssToken::Ident(ident) => {
-                // a:hover のようなセレクタをタイプセレクタとして解釈する
+                // a:hover や p::before のような疑似クラス・疑似要素はサポートしない
+                // a として読むと、hover していない a にもマッチしてしまうので、セレクタ全体を読めないものとする
                 if self.tokenizer.peek() == Some(&CssToken::Colon) {
-                    while self.tokenizer.peek() != Some(&CssToken::OpenCurly) {
-                        self.tokenizer.next();
-                    }
+                    return self.skip_selector();
                 }
 
                 Selector::TypeSelector(ident.to_string())
             },
+            CssToken::OpenSquare => self.consume_attribute_selector(),
             CssToken::AtKeyword(_keyword) => {
                 // @ ではじまるルールはサポートしないので、宣言ブロックの開始直前まで読み捨てる
-                while self.tokenizer.peek() != Some(&CssToken::OpenCurly) {
-                    self.tokenizer.next();
-                }
-
-                Selector::UnknownSelector
+                self.skip_selector()
             },
-            _ => {
-                self.tokenizer.next();
-                Selector::UnknownSelector
+            _ => Selector::UnknownSelector,
+        }
+    }
+
+    // [] 6.3. Attribute selectors | Selectors Level 4
+    // https://www.w3.org/TR/selectors-4/#attribute-selectors
+    // [ の直後から ] までを読む。今のところ [name] と [name=value] だけをサポートする
+    fn consume_attribute_selector(&mut self) -> Selector {
+        self.skip_whitespace();
+        let name = match self.tokenizer.next() {
+            Some(CssToken::Ident(name)) => name,
+            _ => return self.skip_attribute_selector(),
+        };
+
+        self.skip_whitespace();
+        let value = match self.tokenizer.next() {
+            Some(CssToken::CloseSquare) => return Selector::AttributeSelector { name, value: None },
+            Some(CssToken::Delim('=')) => {
+                self.skip_whitespace();
+                match self.tokenizer.next() {
+                    Some(CssToken::StringToken(value)) | Some(CssToken::Ident(value)) => value,
+                    _ => return self.skip_attribute_selector(),
+                }
             }
+            _ => return self.skip_attribute_selector(),
+        };
+
+        self.skip_whitespace();
+        match self.tokenizer.next() {
+            Some(CssToken::CloseSquare) => Selector::AttributeSelector { name, value: Some(value) },
+            _ => self.skip_attribute_selector(),
         }
     }
 
+    // 読めないセレクタの残りを、宣言ブロックの { かセレクタリストの次の , の手前まで読み捨てる
+    fn skip_selector(&mut self) -> Selector {
+        while !matches!(self.tokenizer.peek(), Some(CssToken::OpenCurly) | Some(CssToken::Delim(',')) | None) {
+            self.tokenizer.next();
+        }
+        Selector::UnknownSelector
+    }
+
+    // 読めない属性セレクタは ] まで読み捨てて UnknownSelector にする
+    fn skip_attribute_selector(&mut self) -> Selector {
+        while !matches!(self.tokenizer.peek(), Some(CssToken::CloseSquare) | Some(CssToken::OpenCurly) | None) {
+            self.tokenizer.next();
+        }
+        if self.tokenizer.peek() == Some(&CssToken::CloseSquare) {
+            self.tokenizer.next();
+        }
+        Selector::UnknownSelector
+    }
+
     fn consume_list_of_declarations(&mut self) -> Vec<Declaration> {
         let mut declarations = Vec::new();
 
@@ -119,18 +323,48 @@ impl CssParser {
                     assert_eq!(self.tokenizer.next(), Some(CssToken::CloseCurly));
                     return declarations;
                 }
-                CssToken::SemiColon => {
-                    assert_eq!(self.tokenizer.next(), Some(CssToken::SemiColon));
+                CssToken::SemiColon | CssToken::Whitespace => {
+                    self.tokenizer.next();
                 }
                 CssToken::Ident(ref _ident) => {
                     if let Some(declaration) = self.consume_declaration() {
                         declarations.push(declaration);
                     }
                 }
-                _ => {
+                // declaration の先頭になれないものが来たら、その declaration は丸ごと読み捨てる
+                _ => self.skip_to_next_declaration(),
+            }
+        }
+    }
+
+    // [] 5.4.5. Consume a list of declarations | CSS Syntax Module Level 3
+    // https://www.w3.org/TR/css-syntax-3/#consume-list-of-declarations
+    // ----- Cited From Reference -----
+    // anything else
+    // This is a parse error. Reconsume the current input token. As long as the next input token is anything other than a <semicolon-token> or <EOF-token>, consume a component value and throw away the returned value.
+    // --------------------------------
+    // 壊れた declaration の残りを、次の ; (これも読む) かブロックを閉じる } (これは読まない) まで読み捨てる
+    // 途中の {} () [] は対応がとれるまで中身ごと読み捨てるので、中にある ; や } で止まることはない
+    fn skip_to_next_declaration(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.tokenizer.peek() {
+                None => return,
+                Some(CssToken::SemiColon) if depth == 0 => {
                     self.tokenizer.next();
+                    return;
+                }
+                Some(CssToken::CloseCurly) if depth == 0 => return,
+                Some(CssToken::OpenCurly) | Some(CssToken::OpenParenthesis) | Some(CssToken::OpenSquare) | Some(CssToken::Function(_)) => {
+                    depth += 1;
+                }
+                // 対応する開き括弧のない閉じ括弧は、ただの値として読み飛ばす
+                Some(CssToken::CloseCurly) | Some(CssToken::CloseParenthesis) | Some(CssToken::CloseSquare) if depth > 0 => {
+                    depth -= 1;
                 }
+                Some(_) => {}
             }
+            self.tokenizer.next();
         }
     }
 
@@ -140,30 +374,72 @@ impl CssParser {
         }
 
         let mut declaration = Declaration::new();
-        declaration.set_property(self.consume_ident());
+        declaration.set_property(self.consume_ident()?);
 
-        match self.tokenizer.next() {
-            Some(token) => match token {
-                CssToken::Colon => {}, // declaration は property : value の形をしているはずなのでコロン以外が来たらおかしい
-                _ => return None,
-            },
+        self.skip_whitespace();
+        match self.tokenizer.peek() {
+            Some(CssToken::Colon) => {
+                self.tokenizer.next();
+            }
+            // declaration は property : value の形をしているはずなのでコロン以外が来たらおかしい
+            // 後ろの declaration を巻き込まないよう、この declaration の残りを読み捨てておく
+            Some(_) => {
+                self.skip_to_next_declaration();
+                return None;
+            }
             None => return None,
         }
 
-        declaration.set_value(self.consume_component_value());
+        self.skip_whitespace();
+
+        // ; か } までの component value を (空白を除いて) 値として集める。margin: 10px 20px のように複数あることもある
+        // ! 以降は値ではない。!important があればそれを覚えておき、残りは読み捨てる
+        // https://www.w3.org/TR/css-syntax-3/#consume-declaration
+        let mut values = Vec::new();
+        let mut after_bang = false;
+        loop {
+            match self.tokenizer.peek() {
+                Some(CssToken::SemiColon) | Some(CssToken::CloseCurly) | None => break,
+                Some(CssToken::Delim('!')) => {
+                    after_bang = true;
+                    self.tokenizer.next();
+                    self.skip_whitespace();
+                    if let Some(CssToken::Ident(ident)) = self.tokenizer.peek() {
+                        if ident.eq_ignore_ascii_case("important") {
+                            declaration.set_important(true);
+                        }
+                    }
+                }
+                Some(CssToken::Whitespace) => {
+                    self.tokenizer.next();
+                }
+                Some(_) => {
+                    let token = self.consume_component_value();
+                    if !after_bang {
+                        values.push(token);
+                    }
+                }
+            }
+        }
+        declaration.set_values(values);
 
         Some(declaration)
     }
 
-    fn consume_ident(&mut self) -> String {
-        let token = match self.tokenizer.next() {
-            Some(t) => t,
-            None => panic!("should have a token but got None")
-        };
+    fn skip_whitespace(&mut self) {
+        while self.tokenizer.peek() == Some(&CssToken::Whitespace) {
+            self.tokenizer.next();
+        }
+    }
 
-        match token {
-            CssToken::Ident(i) => i,
-            _ => panic!("Parse error: {:?} is an unexpected token.", token)
+    // 次が識別子ならそれを読む。識別子でなければ何も読まずに None を返す
+    fn consume_ident(&mut self) -> Option<String> {
+        match self.tokenizer.peek() {
+            Some(CssToken::Ident(_)) => match self.tokenizer.next() {
+                Some(CssToken::Ident(i)) => Some(i),
+                _ => None,
+            },
+            _ => None,
         }
     }
 
@@ -172,33 +448,105 @@ impl CssParser {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct StyleSheet {
     pub rules: Vec<QualifiedRule>,
+    pub at_rules: Vec<AtRule>,
 }
 
 impl StyleSheet {
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self { rules: Vec::new(), at_rules: Vec::new() }
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<QualifiedRule>) {
+        self.rules = rules;
+    }
+
+    pub fn set_at_rules(&mut self, at_rules: Vec<AtRule>) {
+        self.at_rules = at_rules;
+    }
+
+    // @import で読み込むよう指定された URL を、書かれた順に返す
+    pub fn imports(&self) -> Vec<String> {
+        self.at_rules.iter().filter_map(|rule| rule.import_url()).collect()
+    }
+
+    // [] 3.4. The @charset Rule | CSS Syntax Module Level 3
+    // https://www.w3.org/TR/css-syntax-3/#charset-rule
+    // 最初の @charset "..."; の文字列。実際のデコードには使っていない
+    pub fn charset(&self) -> Option<String> {
+        match self.at_rules.iter().find(|rule| rule.name == "charset")?.prelude.first() {
+            Some(CssToken::StringToken(s)) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+// @media screen { ... } のようなルール。name は @ を除いたキーワード、prelude は { または ; までのトークン
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtRule {
+    pub name: String,
+    pub prelude: Vec<CssToken>,
+    pub rules: Vec<QualifiedRule>,
+}
+
+impl AtRule {
+    pub fn new(name: String) -> Self {
+        Self { name, prelude: Vec::new(), rules: Vec::new() }
+    }
+
+    pub fn set_prelude(&mut self, prelude: Vec<CssToken>) {
+        self.prelude = prelude;
     }
 
     pub fn set_rules(&mut self, rules: Vec<QualifiedRule>) {
         self.rules = rules;
     }
+
+    // ブロックを持たず ; で終わる at-rule か。@media などのブロックを持つものは false
+    pub fn is_statement(&self) -> bool {
+        matches!(self.name.as_str(), "import" | "charset" | "namespace")
+    }
+
+    // [] 2. Importing Style Sheets: the @import rule | CSS Cascading and Inheritance Level 4
+    // https://www.w3.org/TR/css-cascade-4/#at-import
+    // ----- Cited From Reference -----
+    // @import [ <url> | <string> ] ...
+    // --------------------------------
+    // @import "x.css" と @import url("x.css") の URL。@import でなければ None
+    // url(x.css) のように引用符のない書き方はまだ tokenizer が読めない
+    pub fn import_url(&self) -> Option<String> {
+        if self.name != "import" {
+            return None;
+        }
+
+        match self.prelude.as_slice() {
+            [CssToken::StringToken(url), ..] => Some(url.clone()),
+            [CssToken::Function(f), CssToken::StringToken(url), ..] if f.eq_ignore_ascii_case("url") => Some(url.clone()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct QualifiedRule {
-    pub selector: Selector,
+    pub selectors: Vec<Selector>,
     pub declarations: Vec<Declaration>,
 }
 
 impl QualifiedRule {
     pub fn new() -> Self {
-        Self { selector: Selector::TypeSelector("".to_string()), declarations: Vec::new() }
+        Self { selectors: Vec::new(), declarations: Vec::new() }
     }
 
+    // セレクタが 1 つだけのルールにする
     pub fn set_selector(&mut self, selector: Selector) {
-        self.selector = selector;
+        self.selectors = vec![selector];
+    }
+
+    pub fn set_selectors(&mut self, selectors: Vec<Selector>) {
+        self.selectors = selectors;
     }
 
     pub fn set_declarations(&mut self, declarations: Vec<Declaration>) {
@@ -211,33 +559,95 @@ pub enum Selector {
     TypeSelector(String),
     ClassSelector(String),
     IdSelector(String),
+    // * はすべての要素にマッチする
+    UniversalSelector,
+    // [disabled] のように値がないものは、属性があるかどうかだけを見る
+    AttributeSelector { name: String, value: Option<String> },
+    ComplexSelector(ComplexSelector),
     UnknownSelector,
 }
 
+impl Selector {
+    // [] 17. Calculating a selector's specificity | Selectors Level 4
+    // https://www.w3.org/TR/selectors-4/#specificity-rules
+    // (id セレクタの数, class セレクタの数, type セレクタの数) の組。タプルの大小比較がそのまま優先度の比較になる
+    pub fn specificity(&self) -> (u32, u32, u32) {
+        match self {
+            Selector::IdSelector(_) => (1, 0, 0),
+            Selector::ClassSelector(_) | Selector::AttributeSelector { .. } => (0, 1, 0),
+            Selector::TypeSelector(_) => (0, 0, 1),
+            Selector::UniversalSelector | Selector::UnknownSelector => (0, 0, 0),
+            Selector::ComplexSelector(complex) => complex.parts.iter().fold((0, 0, 0), |acc, part| {
+                let (a, b, c) = part.specificity();
+                (acc.0 + a, acc.1 + b, acc.2 + c)
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Combinator {
+    // div p のような空白
+    Descendant,
+    // ul > li のような >
+    Child,
+}
+
+// parts[i] と parts[i + 1] が combinators[i] でつながっている。なので combinators は parts より 1 つ少ない
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexSelector {
+    pub parts: Vec<Selector>,
+    pub combinators: Vec<Combinator>,
+}
+
+impl ComplexSelector {
+    pub fn new(first: Selector) -> Self {
+        Self { parts: vec![first], combinators: Vec::new() }
+    }
+
+    pub fn push(&mut self, combinator: Combinator, selector: Selector) {
+        self.combinators.push(combinator);
+        self.parts.push(selector);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Declaration {
     pub property: String,
-    pub value: CssToken,
+    pub values: Vec<CssToken>,
+    pub important: bool,
 }
 
 impl Declaration {
     pub fn new() -> Self {
-        Self { property: String::new(), value: CssToken::Ident(String::new()) }
+        Self { property: String::new(), values: Vec::new(), important: false }
+    }
+
+    pub fn set_important(&mut self, important: bool) {
+        self.important = important;
     }
 
     pub fn set_property(&mut self, property: String) {
         self.property = property;
     }
 
+    // 値が 1 つしかないプロパティはこれだけ見ればよい。値が空なら None
+    pub fn value(&self) -> Option<&CssToken> {
+        self.values.first()
+    }
+
     pub fn set_value(&mut self, value: CssToken) {
-        self.value = value;
+        self.values = vec![value];
+    }
+
+    pub fn set_values(&mut self, values: Vec<CssToken>) {
+        self.values = values;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloc::vec;
 
     #[test]
     fn test_empty() {
@@ -349,4 +759,389 @@ mod tests {
             i += 1;
         }
     }
+
+    #[test]
+    fn test_media_rule() {
+        let style = "@media screen { p { color: red; } } h1 { color: blue; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut rule1 = QualifiedRule::new();
+        rule1.set_selector(Selector::TypeSelector("p".to_string()));
+        let mut declaration1 = Declaration::new();
+        declaration1.set_property("color".to_string());
+        declaration1.set_value(CssToken::Ident("red".to_string()));
+        rule1.set_declarations(vec![declaration1]);
+
+        let mut at_rule = AtRule::new("media".to_string());
+        at_rule.set_prelude(vec![CssToken::Ident("screen".to_string())]);
+        at_rule.set_rules(vec![rule1]);
+
+        let mut rule2 = QualifiedRule::new();
+        rule2.set_selector(Selector::TypeSelector("h1".to_string()));
+        let mut declaration2 = Declaration::new();
+        declaration2.set_property("color".to_string());
+        declaration2.set_value(CssToken::Ident("blue".to_string()));
+        rule2.set_declarations(vec![declaration2]);
+
+        assert_eq!(cssom.at_rules, vec![at_rule]);
+        assert_eq!(cssom.rules, vec![rule2]);
+    }
+
+    #[test]
+    fn test_nested_media_rules() {
+        let style = "@media screen and (min-width: 100px) { p { color: red; } .a { color: blue; } } @import \"a.css\"; @font-face { font-family: x; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 0);
+        assert_eq!(cssom.at_rules.len(), 3);
+
+        let media = &cssom.at_rules[0];
+        assert_eq!(media.name, "media");
+        assert_eq!(media.prelude[0], CssToken::Ident("screen".to_string()));
+        assert_eq!(media.prelude.last(), Some(&CssToken::CloseParenthesis));
+        assert_eq!(media.rules.len(), 2);
+        assert_eq!(media.rules[0].selectors, vec![Selector::TypeSelector("p".to_string())]);
+        assert_eq!(media.rules[1].selectors, vec![Selector::ClassSelector("a".to_string())]);
+
+        let import = &cssom.at_rules[1];
+        assert_eq!(import.name, "import");
+        assert_eq!(import.prelude, vec![CssToken::StringToken("a.css".to_string())]);
+        assert_eq!(import.rules.len(), 0);
+
+        let font_face = &cssom.at_rules[2];
+        assert_eq!(font_face.name, "font-face");
+        assert_eq!(font_face.prelude.len(), 0);
+        assert_eq!(font_face.rules.len(), 0);
+    }
+
+    #[test]
+    fn test_import_and_charset() {
+        let style = "@charset \"utf-8\"; @import \"x.css\"; @import url(\"y.css\") screen; p { } @media print { a { } }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.charset(), Some("utf-8".to_string()));
+        assert_eq!(cssom.imports(), vec!["x.css".to_string(), "y.css".to_string()]);
+
+        // ; で終わる at-rule が後ろのルールを飲み込まない
+        assert_eq!(cssom.rules.len(), 1);
+        assert_eq!(cssom.rules[0].selectors, vec![Selector::TypeSelector("p".to_string())]);
+
+        let statements: Vec<bool> = cssom.at_rules.iter().map(|rule| rule.is_statement()).collect();
+        assert_eq!(statements, vec![true, true, true, false]);
+        assert_eq!(cssom.at_rules[3].rules.len(), 1);
+    }
+
+    #[test]
+    fn test_no_import() {
+        let style = "p { }".to_string();
+        let cssom = CssParser::new(CssTokenizer::new(style)).parse_stylesheet();
+
+        assert!(cssom.imports().is_empty());
+        assert_eq!(cssom.charset(), None);
+    }
+
+    #[test]
+    fn test_universal_selector() {
+        let style = "* { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut rule = QualifiedRule::new();
+        rule.set_selector(Selector::UniversalSelector);
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("red".to_string()));
+        rule.set_declarations(vec![declaration]);
+
+        assert_eq!(cssom.rules, vec![rule]);
+    }
+
+    #[test]
+    fn test_selector_list() {
+        let style = "h1, p { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut rule = QualifiedRule::new();
+        rule.set_selectors(vec![
+            Selector::TypeSelector("h1".to_string()),
+            Selector::TypeSelector("p".to_string()),
+        ]);
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("red".to_string()));
+        rule.set_declarations(vec![declaration]);
+
+        assert_eq!(cssom.rules, vec![rule.clone()]);
+
+        // 疑似クラスのついたセレクタは読めないが、リストの次のセレクタは読む
+        let style = "h1:hover, p { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+        rule.set_selectors(vec![Selector::UnknownSelector, Selector::TypeSelector("p".to_string())]);
+        assert_eq!(cssom.rules, vec![rule]);
+    }
+
+    #[test]
+    fn test_descendant_combinator() {
+        let style = "div p { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut selector = ComplexSelector::new(Selector::TypeSelector("div".to_string()));
+        selector.push(Combinator::Descendant, Selector::TypeSelector("p".to_string()));
+
+        assert_eq!(cssom.rules.len(), 1);
+        assert_eq!(cssom.rules[0].selectors, vec![Selector::ComplexSelector(selector)]);
+    }
+
+    #[test]
+    fn test_child_combinator() {
+        let style = "ul > li, #a .b>c { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut selector1 = ComplexSelector::new(Selector::TypeSelector("ul".to_string()));
+        selector1.push(Combinator::Child, Selector::TypeSelector("li".to_string()));
+        let mut selector2 = ComplexSelector::new(Selector::IdSelector("a".to_string()));
+        selector2.push(Combinator::Descendant, Selector::ClassSelector("b".to_string()));
+        selector2.push(Combinator::Child, Selector::TypeSelector("c".to_string()));
+
+        assert_eq!(cssom.rules.len(), 1);
+        assert_eq!(
+            cssom.rules[0].selectors,
+            vec![Selector::ComplexSelector(selector1), Selector::ComplexSelector(selector2)]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_selectors() {
+        // 複合セレクタの一部だけを読んで p にしてしまわない
+        let cssom = CssParser::new(CssTokenizer::new("p.w, div { display: none }".to_string())).parse_stylesheet();
+        assert_eq!(cssom.rules.len(), 1);
+        assert_eq!(
+            cssom.rules[0].selectors,
+            vec![Selector::UnknownSelector, Selector::TypeSelector("div".to_string())]
+        );
+        assert_eq!(cssom.rules[0].declarations.len(), 1);
+
+        // 結合子の後ろや . の後ろにセレクタがなくても panic せず、宣言ブロックは読める
+        for style in ["p > { color: red }", ".{ color: red }", "p ~ a { color: red }"] {
+            let cssom = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+            assert_eq!(cssom.rules.len(), 1, "{}", style);
+            assert_eq!(cssom.rules[0].selectors, vec![Selector::UnknownSelector], "{}", style);
+            assert_eq!(cssom.rules[0].declarations.len(), 1, "{}", style);
+        }
+
+        // 疑似クラスや疑似要素を、その前のタイプセレクタとして読まない
+        for style in ["a:hover { color: red }", "p::before { color: red }", "div a:hover { color: red }"] {
+            let cssom = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+            assert_eq!(cssom.rules.len(), 1, "{}", style);
+            assert_eq!(cssom.rules[0].selectors, vec![Selector::UnknownSelector], "{}", style);
+            assert_eq!(cssom.rules[0].declarations.len(), 1, "{}", style);
+        }
+
+        // 入力がセレクタの途中で終わっても panic しない
+        for style in ["p >", "."] {
+            let cssom = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+            assert!(cssom.rules.is_empty(), "{}", style);
+        }
+    }
+
+    #[test]
+    fn test_child_combinator_rule() {
+        let style = "ul > li { color: red }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut selector = ComplexSelector::new(Selector::TypeSelector("ul".to_string()));
+        selector.push(Combinator::Child, Selector::TypeSelector("li".to_string()));
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("red".to_string()));
+
+        // > の前後が UnknownSelector にならず、1 つの complex selector になる
+        assert_eq!(cssom.rules.len(), 1);
+        assert_eq!(cssom.rules[0].selectors, vec![Selector::ComplexSelector(selector)]);
+        assert_eq!(cssom.rules[0].declarations, vec![declaration]);
+    }
+
+    #[test]
+    fn test_parse_declarations() {
+        let style = "color: green; bad; display : none".to_string();
+        let declarations = CssParser::new(CssTokenizer::new(style)).parse_declarations();
+
+        let mut declaration1 = Declaration::new();
+        declaration1.set_property("color".to_string());
+        declaration1.set_value(CssToken::Ident("green".to_string()));
+        let mut declaration2 = Declaration::new();
+        declaration2.set_property("display".to_string());
+        declaration2.set_value(CssToken::Ident("none".to_string()));
+        assert_eq!(declarations, vec![declaration1, declaration2]);
+    }
+
+    #[test]
+    fn test_important() {
+        let style = "p { color: red !important; font-size: 40px; margin: 0 ! important }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut declaration1 = Declaration::new();
+        declaration1.set_property("color".to_string());
+        declaration1.set_value(CssToken::Ident("red".to_string()));
+        declaration1.set_important(true);
+        let mut declaration2 = Declaration::new();
+        declaration2.set_property("font-size".to_string());
+        declaration2.set_value(CssToken::Dimension(40.0, "px".to_string()));
+        let mut declaration3 = Declaration::new();
+        declaration3.set_property("margin".to_string());
+        declaration3.set_value(CssToken::Number(0.0));
+        declaration3.set_important(true);
+
+        assert_eq!(cssom.rules.len(), 1);
+        assert_eq!(cssom.rules[0].declarations, vec![declaration1, declaration2, declaration3]);
+        assert!(cssom.rules[0].declarations[0].important);
+        assert!(!cssom.rules[0].declarations[1].important);
+    }
+
+    #[test]
+    fn test_specificity() {
+        let id = Selector::IdSelector("a".to_string());
+        let class = Selector::ClassSelector("a".to_string());
+        let type_selector = Selector::TypeSelector("a".to_string());
+
+        assert_eq!(id.specificity(), (1, 0, 0));
+        assert_eq!(class.specificity(), (0, 1, 0));
+        assert_eq!(type_selector.specificity(), (0, 0, 1));
+        assert_eq!(Selector::UniversalSelector.specificity(), (0, 0, 0));
+        assert_eq!(Selector::UnknownSelector.specificity(), (0, 0, 0));
+
+        let mut complex = ComplexSelector::new(Selector::IdSelector("a".to_string()));
+        complex.push(Combinator::Descendant, Selector::ClassSelector("b".to_string()));
+        complex.push(Combinator::Child, Selector::TypeSelector("c".to_string()));
+        assert_eq!(Selector::ComplexSelector(complex).specificity(), (1, 1, 1));
+
+        assert!(id.specificity() > class.specificity());
+        assert!(class.specificity() > type_selector.specificity());
+        assert!(type_selector.specificity() > Selector::UniversalSelector.specificity());
+    }
+
+    #[test]
+    fn test_stylesheet_eq() {
+        let style = "p { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        let mut rule = QualifiedRule::new();
+        rule.set_selector(Selector::TypeSelector("p".to_string()));
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("red".to_string()));
+        rule.set_declarations(vec![declaration]);
+        let mut expected = StyleSheet::new();
+        expected.set_rules(vec![rule]);
+
+        assert_eq!(expected, cssom);
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        let parse = |s: &str| CssParser::new(CssTokenizer::new(s.to_string())).parse_selector();
+
+        assert_eq!(Some(Selector::TypeSelector("p".to_string())), parse("p"));
+        assert_eq!(Some(Selector::ClassSelector("a".to_string())), parse(" .a "));
+        assert_eq!(Some(Selector::IdSelector("x".to_string())), parse("#x"));
+        assert_eq!(Some(Selector::UniversalSelector), parse("*"));
+        assert_eq!(None, parse(""));
+        assert_eq!(None, parse("."));
+        assert_eq!(None, parse("p {"));
+        assert_eq!(None, parse("#"));
+        assert_eq!(None, parse("div p"));
+        assert_eq!(None, parse("ul > li"));
+        assert_eq!(None, parse("p.a"));
+        assert_eq!(None, parse("a:hover"));
+        assert_eq!(None, parse("p, a"));
+    }
+
+    #[test]
+    fn test_attribute_selector() {
+        let style = "[disabled] { color: red; } [type=\"text\"] { color: blue; } [ lang = en ] { color: green; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 3);
+        assert_eq!(
+            cssom.rules[0].selectors,
+            vec![Selector::AttributeSelector { name: "disabled".to_string(), value: None }]
+        );
+        assert_eq!(
+            cssom.rules[1].selectors,
+            vec![Selector::AttributeSelector { name: "type".to_string(), value: Some("text".to_string()) }]
+        );
+        assert_eq!(
+            cssom.rules[2].selectors,
+            vec![Selector::AttributeSelector { name: "lang".to_string(), value: Some("en".to_string()) }]
+        );
+    }
+
+    #[test]
+    fn test_multiple_values() {
+        let style = "p { margin: 10px 20px !important; color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 1);
+        let declarations = &cssom.rules[0].declarations;
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(
+            declarations[0].values,
+            vec![CssToken::Dimension(10.0, "px".to_string()), CssToken::Dimension(20.0, "px".to_string())]
+        );
+        assert_eq!(declarations[0].value(), Some(&CssToken::Dimension(10.0, "px".to_string())));
+        assert!(declarations[0].important);
+        assert_eq!(declarations[1].values, vec![CssToken::Ident("red".to_string())]);
+    }
+
+    #[test]
+    fn test_skip_invalid_declaration() {
+        let style = "p { bad; color: red } h1 { bad } h2 { x y: (a; b) {c; d}; color: blue; 1: 2; display: none }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 3);
+
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("red".to_string()));
+        assert_eq!(cssom.rules[0].declarations, vec![declaration]);
+
+        // 壊れた declaration がブロックの最後にあっても、閉じ括弧は食べない
+        assert_eq!(cssom.rules[1].selectors, vec![Selector::TypeSelector("h1".to_string())]);
+        assert!(cssom.rules[1].declarations.is_empty());
+
+        // 括弧の中の ; や } では止まらない
+        assert_eq!(cssom.rules[2].declarations.len(), 2);
+        assert_eq!(cssom.rules[2].declarations[0].property, "color");
+        assert_eq!(cssom.rules[2].declarations[1].property, "display");
+
+        // 対応する開き括弧のない閉じ括弧があっても、次の ; で止まる
+        let style = "p { x ) ; color: red } h1 { color: blue }".to_string();
+        let t = CssTokenizer::new(style);
+        let cssom = CssParser::new(t).parse_stylesheet();
+
+        assert_eq!(cssom.rules.len(), 2);
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("red".to_string()));
+        assert_eq!(cssom.rules[0].declarations, vec![declaration]);
+
+        assert_eq!(cssom.rules[1].selectors, vec![Selector::TypeSelector("h1".to_string())]);
+        let mut declaration = Declaration::new();
+        declaration.set_property("color".to_string());
+        declaration.set_value(CssToken::Ident("blue".to_string()));
+        assert_eq!(cssom.rules[1].declarations, vec![declaration]);
+    }
 }
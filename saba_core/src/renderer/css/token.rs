@@ -6,15 +6,21 @@ pub enum CssToken {
     HashToken(String),
     Delim(char),
     Number(f64),
+    Dimension(f64, String),
+    Percentage(f64),
     Colon,
     SemiColon,
     OpenParenthesis,
     CloseParenthesis,
     OpenCurly,
     CloseCurly,
+    OpenSquare,
+    CloseSquare,
     Ident(String),
     StringToken(String),
     AtKeyword(String),
+    Function(String),
+    Whitespace,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,6 +34,11 @@ impl CssTokenizer {
         Self { pos: 0, input: css.chars().collect() }
     }
 
+    // 最後までトークナイズして、トークンをまとめて Vec で返す
+    pub fn tokenize(self) -> Vec<CssToken> {
+        self.collect()
+    }
+
     // 文字列トークンを [start] の引用符でスキャンし、閉じ引用符の位置を返す
     fn scan_string_at(input: &[char], start: usize) -> (String, usize) {
         let ending = input[start];
@@ -42,13 +53,42 @@ impl CssTokenizer {
         (s, pos)
     }
 
+    // [] 4.3.10. Check if three code points would start a number | CSS Syntax Module Level 3
+    // https://www.w3.org/TR/css-syntax-3/#starts-with-a-number
+    // ----- Cited From Reference -----
+    // U+002B PLUS SIGN (+) / U+002D HYPHEN-MINUS (-): If the second code point is a digit, return true. Otherwise, if the second code point is a U+002E FULL STOP (.) and the third code point is a digit, return true. Otherwise, return false.
+    // U+002E FULL STOP (.): If the second code point is a digit, return true. Otherwise, return false.
+    // digit: Return true.
+    // --------------------------------
+    // -webkit-foo のように - の後ろが英字なら識別子になる
+    fn starts_with_number(input: &[char], start: usize) -> bool {
+        let is_digit = |pos: usize| input.get(pos).map(|c| c.is_ascii_digit()).unwrap_or(false);
+        match input.get(start) {
+            Some('+') | Some('-') => is_digit(start + 1) || (input.get(start + 1) == Some(&'.') && is_digit(start + 2)),
+            Some('.') => is_digit(start + 1),
+            Some(c) => c.is_ascii_digit(),
+            None => false,
+        }
+    }
+
     // 数値トークンを [start] からスキャンし、終端位置を返す
+    // 先頭の符号と、1e3 のような指数表記も読む
     fn scan_numeric_at(input: &[char], start: usize) -> (f64, usize) {
         let mut num = 0f64;
         let mut floating = false;
         let mut factor = 1f64;
         let mut pos = start;
 
+        let mut sign = 1f64;
+        match input.get(pos) {
+            Some('+') => pos += 1,
+            Some('-') => {
+                sign = -1f64;
+                pos += 1;
+            }
+            _ => {}
+        }
+
         while pos < input.len() {
             match input[pos] {
                 '0'..='9' => {
@@ -61,14 +101,42 @@ impl CssTokenizer {
                     }
                     pos += 1;
                 }
-                '.' if !floating => {
+                '.' if !floating && input.get(pos + 1).map(|c| c.is_ascii_digit()).unwrap_or(false) => {
                     floating = true;
                     pos += 1;
                 }
                 _ => break,
             }
         }
-        (num, pos)
+
+        // 指数部。1em のような単位と区別するため、e の後ろに (符号と) 数字が続くときだけ指数とみなす
+        if let Some('e') | Some('E') = input.get(pos) {
+            let (exponent_sign, digits_start) = match input.get(pos + 1) {
+                Some('+') => (1, pos + 2),
+                Some('-') => (-1, pos + 2),
+                _ => (1, pos + 1),
+            };
+
+            if input.get(digits_start).map(|c| c.is_ascii_digit()).unwrap_or(false) {
+                let mut exponent = 0i32;
+                pos = digits_start;
+                while let Some(d) = input.get(pos).and_then(|c| c.to_digit(10)) {
+                    exponent = exponent.saturating_mul(10).saturating_add(d as i32);
+                    pos += 1;
+                }
+
+                // no_std だと powi が使えないので自分で掛ける。f64 はどのみち 10^400 あたりで溢れるので、それ以上は回さない
+                for _ in 0..exponent.min(400) {
+                    if exponent_sign > 0 {
+                        num *= 10.0;
+                    } else {
+                        num /= 10.0;
+                    }
+                }
+            }
+        }
+
+        (sign * num, pos)
     }
 
     // 識別子トークンを [start] からスキャンし、終端位置を返す
@@ -86,6 +154,41 @@ impl CssTokenizer {
         }
         (s, pos)
     }
+
+    // 空白とコメントを [start] から読み飛ばし、空白があったかどうかと終端位置を返す
+    // コメントは */ まで読み飛ばす。閉じられていなければ入力の最後まで
+    fn scan_whitespace_and_comments_at(input: &[char], start: usize) -> (bool, usize) {
+        let mut has_whitespace = false;
+        let mut pos = start;
+        while pos < input.len() {
+            if input[pos].is_whitespace() {
+                has_whitespace = true;
+                pos += 1;
+            } else if input[pos] == '/' && input.get(pos + 1) == Some(&'*') {
+                pos += 2;
+                while pos < input.len() {
+                    if input[pos] == '*' && input.get(pos + 1) == Some(&'/') {
+                        pos += 2;
+                        break;
+                    }
+                    pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+        (has_whitespace, pos)
+    }
+
+    // 識別子の直後に ( が続いていれば rgb( のような関数記法なので Function にする
+    fn ident_like_token(&mut self, ident: String) -> CssToken {
+        if self.input.get(self.pos) == Some(&'(') {
+            self.pos += 1;
+            CssToken::Function(ident)
+        } else {
+            CssToken::Ident(ident)
+        }
+    }
 }
 
 impl Iterator for CssTokenizer {
@@ -97,35 +200,57 @@ impl Iterator for CssTokenizer {
         while self.pos < input.len() {
             let c = input[self.pos];
 
-            // 空白をスキップ
-            if c.is_whitespace() {
-                self.pos += 1;
+            // 空白とコメントの連続はまとめて 1 つの Whitespace にする。.a .b と .a.b を区別するのに必要
+            // ただし入力の先頭と末尾の空白は意味がないので捨てる
+            if c.is_whitespace() || (c == '/' && input.get(self.pos + 1) == Some(&'*')) {
+                let start = self.pos;
+                let (has_whitespace, next_pos) = Self::scan_whitespace_and_comments_at(input, self.pos);
+                self.pos = next_pos;
+                if has_whitespace && start != 0 && self.pos < input.len() {
+                    return Some(CssToken::Whitespace);
+                }
                 continue;
             }
 
             let token = match c {
+                _ if Self::starts_with_number(input, self.pos) => {
+                    let (num, next_pos) = Self::scan_numeric_at(input, self.pos);
+                    self.pos = next_pos;
+
+                    // 数値の直後に空白を挟まず識別子が続くなら 40px のような dimension、% が続くなら percentage
+                    if input.get(self.pos).map(|c| c.is_ascii_alphabetic() || *c == '_').unwrap_or(false) {
+                        let (unit, next_pos) = Self::scan_ident_at(input, self.pos);
+                        self.pos = next_pos;
+                        CssToken::Dimension(num, unit)
+                    } else if input.get(self.pos) == Some(&'%') {
+                        self.pos += 1;
+                        CssToken::Percentage(num)
+                    } else {
+                        CssToken::Number(num)
+                    }
+                }
                 '(' => { self.pos += 1; CssToken::OpenParenthesis }
                 ')' => { self.pos += 1; CssToken::CloseParenthesis }
                 ',' => { self.pos += 1; CssToken::Delim(',') }
                 '.' => { self.pos += 1; CssToken::Delim('.') }
+                // セレクタの結合子。+ の後ろに数字が続くときは符号付きの数値として上で処理している
+                '>' => { self.pos += 1; CssToken::Delim('>') }
+                '+' => { self.pos += 1; CssToken::Delim('+') }
+                '~' => { self.pos += 1; CssToken::Delim('~') }
+                '*' => { self.pos += 1; CssToken::Delim('*') }
+                '!' => { self.pos += 1; CssToken::Delim('!') }
                 ':' => { self.pos += 1; CssToken::Colon }
                 ';' => { self.pos += 1; CssToken::SemiColon }
                 '{' => { self.pos += 1; CssToken::OpenCurly }
                 '}' => { self.pos += 1; CssToken::CloseCurly }
-                ' ' | '\n' => {
-                    self.pos += 1;
-                    continue;
-                }
+                '[' => { self.pos += 1; CssToken::OpenSquare }
+                ']' => { self.pos += 1; CssToken::CloseSquare }
+                '=' => { self.pos += 1; CssToken::Delim('=') }
                 '"' | '\'' => {
                     let (s, next_pos) = Self::scan_string_at(input, self.pos);
                     self.pos = next_pos;
                     CssToken::StringToken(s)
                 }
-                '0'..='9' => {
-                    let (num, next_pos) = Self::scan_numeric_at(input, self.pos);
-                    self.pos = next_pos;
-                    CssToken::Number(num)
-                }
                 '#' => {
                     let (ident, next_pos) = Self::scan_ident_at(input, self.pos);
                     self.pos = next_pos;
@@ -134,7 +259,7 @@ impl Iterator for CssTokenizer {
                 '-' => {
                     let (ident, next_pos) = Self::scan_ident_at(input, self.pos);
                     self.pos = next_pos;
-                    CssToken::Ident(ident)
+                    self.ident_like_token(ident)
                 }
                 '@' => {
                     // 次が英字なら at-keyword
@@ -150,10 +275,17 @@ impl Iterator for CssTokenizer {
                 c if c.is_ascii_alphabetic() || c == '_' => {
                     let (ident, next_pos) = Self::scan_ident_at(input, self.pos);
                     self.pos = next_pos;
-                    CssToken::Ident(ident)
+                    self.ident_like_token(ident)
                 }
+                // [] 4.3.1. Consume a token | CSS Syntax Module Level 3
+                // https://www.w3.org/TR/css-syntax-3/#consume-token
+                // ----- Cited From Reference -----
+                // anything else: Return a <delim-token> with its value set to the current input code point.
+                // --------------------------------
+                // 知らない文字で止まらないように Delim にして、どう扱うかはパーサに任せる
                 _ => {
-                    unimplemented!("char {} is not supported yet", c)
+                    self.pos += 1;
+                    CssToken::Delim(c)
                 }
             };
 
@@ -168,6 +300,7 @@ impl Iterator for CssTokenizer {
 mod tests {
     use super::*;
     use alloc::string::ToString;
+    use alloc::vec;
 
     #[test]
     fn test_empty() {
@@ -182,11 +315,15 @@ mod tests {
         let mut t = CssTokenizer::new(style);
         let expected = [
             CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("red".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -195,17 +332,55 @@ mod tests {
         assert!(t.next().is_none());
     }
 
+    #[test]
+    fn test_unknown_char_is_delim() {
+        let style = "12px/1.5 $".to_string();
+        let expected = vec![
+            CssToken::Dimension(12.0, "px".to_string()),
+            CssToken::Delim('/'),
+            CssToken::Number(1.5),
+            CssToken::Whitespace,
+            CssToken::Delim('$'),
+        ];
+        assert_eq!(expected, CssTokenizer::new(style).tokenize());
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let style = "p { color: red; }".to_string();
+        let expected = vec![
+            CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Ident("red".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        assert_eq!(expected, CssTokenizer::new(style).tokenize());
+
+        assert!(CssTokenizer::new("".to_string()).tokenize().is_empty());
+    }
+
     #[test]
     fn test_id_selector() {
         let style = "#id { color: red; }".to_string();
         let mut t = CssTokenizer::new(style);
         let expected = [
             CssToken::HashToken("#id".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("red".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -221,11 +396,15 @@ mod tests {
         let expected = [
             CssToken::Delim('.'),
             CssToken::Ident("class".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("red".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -236,27 +415,72 @@ mod tests {
 
     #[test]
     fn test_multiple_rules() {
-        // The value like "40px" is not supported yet.
         let style = "p { content: \"Hey\"; } h1 { font-size: 40; color: blue; }".to_string();
         let mut t = CssTokenizer::new(style);
         let expected = [
             CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("content".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::StringToken("Hey".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
+            CssToken::Whitespace,
             CssToken::Ident("h1".to_string()),
+            CssToken::Whitespace,
             CssToken::OpenCurly,
+            CssToken::Whitespace,
             CssToken::Ident("font-size".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Number(40.0),
             CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::Ident("color".to_string()),
             CssToken::Colon,
+            CssToken::Whitespace,
             CssToken::Ident("blue".to_string()),
             CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_dimension() {
+        let style = "h1 { font-size: 40px; margin: 1.5em; width: 40; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("h1".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("font-size".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Dimension(40.0, "px".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::Ident("margin".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Dimension(1.5, "em".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::Ident("width".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Number(40.0),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
             CssToken::CloseCurly,
         ];
         for e in expected {
@@ -264,4 +488,306 @@ mod tests {
         }
         assert!(t.next().is_none());
     }
+
+    #[test]
+    fn test_percentage() {
+        let style = "div { width: 50%; height: 33.3%; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("div".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("width".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Percentage(50.0),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::Ident("height".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Percentage(33.3),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_comment() {
+        let style = "p { /* c */ color: red; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Ident("red".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        let style = "p { color: red; } /* c".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Ident("red".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_function() {
+        let style = "p { color: rgb(255, 0, 0); transform: translateX(10px); border-color: rgb; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Function("rgb".to_string()),
+            CssToken::Number(255.0),
+            CssToken::Delim(','),
+            CssToken::Whitespace,
+            CssToken::Number(0.0),
+            CssToken::Delim(','),
+            CssToken::Whitespace,
+            CssToken::Number(0.0),
+            CssToken::CloseParenthesis,
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::Ident("transform".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Function("translateX".to_string()),
+            CssToken::Dimension(10.0, "px".to_string()),
+            CssToken::CloseParenthesis,
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::Ident("border-color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Ident("rgb".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_combinators() {
+        let style = "a > b, a + b, a ~ b { color: red; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("a".to_string()),
+            CssToken::Whitespace,
+            CssToken::Delim('>'),
+            CssToken::Whitespace,
+            CssToken::Ident("b".to_string()),
+            CssToken::Delim(','),
+            CssToken::Whitespace,
+            CssToken::Ident("a".to_string()),
+            CssToken::Whitespace,
+            CssToken::Delim('+'),
+            CssToken::Whitespace,
+            CssToken::Ident("b".to_string()),
+            CssToken::Delim(','),
+            CssToken::Whitespace,
+            CssToken::Ident("a".to_string()),
+            CssToken::Whitespace,
+            CssToken::Delim('~'),
+            CssToken::Whitespace,
+            CssToken::Ident("b".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Ident("red".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        for e in expected {
+            assert_eq!(Some(e.clone()), t.next());
+        }
+        assert!(t.next().is_none());
+    }
+
+    #[test]
+    fn test_whitespace_between_selectors() {
+        let style = ".a .b".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Delim('.'),
+            CssToken::Ident("a".to_string()),
+            CssToken::Whitespace,
+            CssToken::Delim('.'),
+            CssToken::Ident("b".to_string()),
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+
+        let style = ".a.b".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Delim('.'),
+            CssToken::Ident("a".to_string()),
+            CssToken::Delim('.'),
+            CssToken::Ident("b".to_string()),
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_leading_and_trailing_whitespace() {
+        let style = "  /* c */ p {}\n\n".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::CloseCurly,
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_universal_selector() {
+        let style = "* { color: red; }".to_string();
+        let mut t = CssTokenizer::new(style);
+        assert_eq!(Some(CssToken::Delim('*')), t.next());
+        assert_eq!(Some(CssToken::Whitespace), t.next());
+        assert_eq!(Some(CssToken::OpenCurly), t.next());
+    }
+
+    #[test]
+    fn test_signed_and_scientific_numbers() {
+        let style = "-5 +2.5 1e3 2E-2 -foo -webkit-foo 1em".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Number(-5.0),
+            CssToken::Whitespace,
+            CssToken::Number(2.5),
+            CssToken::Whitespace,
+            CssToken::Number(1000.0),
+            CssToken::Whitespace,
+            CssToken::Number(0.02),
+            CssToken::Whitespace,
+            CssToken::Ident("-foo".to_string()),
+            CssToken::Whitespace,
+            CssToken::Ident("-webkit-foo".to_string()),
+            CssToken::Whitespace,
+            CssToken::Dimension(1.0, "em".to_string()),
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_attribute_selector() {
+        let style = "[type=\"text\"]".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::OpenSquare,
+            CssToken::Ident("type".to_string()),
+            CssToken::Delim('='),
+            CssToken::StringToken("text".to_string()),
+            CssToken::CloseSquare,
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_unquoted_attribute_selector() {
+        let style = "input[type=text] { color: red; }".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("input".to_string()),
+            CssToken::OpenSquare,
+            CssToken::Ident("type".to_string()),
+            CssToken::Delim('='),
+            CssToken::Ident("text".to_string()),
+            CssToken::CloseSquare,
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("color".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Ident("red".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_leading_dot_number() {
+        let style = "p { opacity: .5; margin: .25em; } .class { }".to_string();
+        let t = CssTokenizer::new(style);
+        let expected = [
+            CssToken::Ident("p".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::Ident("opacity".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Number(0.5),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::Ident("margin".to_string()),
+            CssToken::Colon,
+            CssToken::Whitespace,
+            CssToken::Dimension(0.25, "em".to_string()),
+            CssToken::SemiColon,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+            CssToken::Whitespace,
+            // 後ろに数字が続かない . は class selector の . のまま
+            CssToken::Delim('.'),
+            CssToken::Ident("class".to_string()),
+            CssToken::Whitespace,
+            CssToken::OpenCurly,
+            CssToken::Whitespace,
+            CssToken::CloseCurly,
+        ];
+        assert_eq!(expected.to_vec(), t.collect::<Vec<_>>());
+    }
 }
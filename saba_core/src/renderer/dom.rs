@@ -1 +1,2 @@
 pub mod node;
+pub mod api;
\ No newline at end of file
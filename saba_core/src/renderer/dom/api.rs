@@ -0,0 +1,289 @@
+use core::cell::RefCell;
+
+use alloc::{
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::renderer::{
+    css::{
+        cssom::{CssParser, Selector},
+        token::CssTokenizer,
+    },
+    dom::node::{dom_iter, ElementKind, Node, NodeKind},
+};
+
+// [] 4.2.4. Mixin NonElementParentNode | DOM Standard
+// https://dom.spec.whatwg.org/#dom-nonelementparentnode-getelementbyid
+// ----- Cited From Reference -----
+// The getElementById(elementId) method steps are to return the first element, in tree order, within this’s descendants, whose ID is elementId; otherwise, if there is no such element, null.
+// --------------------------------
+// Document に生やすのではなく、任意のノードを根として探せるように関数にしておく
+pub fn get_element_by_id(root: &Rc<RefCell<Node>>, id: &str) -> Option<Rc<RefCell<Node>>> {
+    dom_iter(root).find(|node| match node.borrow().kind {
+        NodeKind::Element(ref e) => e.id().as_deref() == Some(id),
+        _ => false,
+    })
+}
+
+// [] 4.4. Interface Document | DOM Standard
+// https://dom.spec.whatwg.org/#dom-document-getelementsbytagname
+// ----- Cited From Reference -----
+// The getElementsByTagName(qualifiedName) method steps are to return the list of elements with qualified name qualifiedName for this.
+// --------------------------------
+// 本当は live な HTMLCollection を返すのだが、呼んだ時点のスナップショットを Vec で返す
+pub fn get_elements_by_tag_name(root: &Rc<RefCell<Node>>, tag: &str) -> Vec<Rc<RefCell<Node>>> {
+    let kind = ElementKind::from(tag);
+
+    dom_iter(root)
+        .filter(|node| node.borrow().get_element_kind().as_ref() == Some(&kind))
+        .collect()
+}
+
+// [] 3. Selector Syntax and Structure | Selectors Level 4
+// https://www.w3.org/TR/selectors-4/#match-against-element
+// 要素がセレクタにマッチするか。要素でないノードには何もマッチしない
+// 結合子を含むセレクタはまだサポートしていない
+pub fn selector_matches(selector: &Selector, node: &Rc<RefCell<Node>>) -> bool {
+    let element = match node.borrow().kind {
+        NodeKind::Element(ref e) => e.clone(),
+        _ => return false,
+    };
+
+    match selector {
+        Selector::TypeSelector(name) => element.tag_name() == *name,
+        Selector::ClassSelector(class) => element.class_list().contains(class),
+        Selector::IdSelector(id) => element.id().as_deref() == Some(id.as_str()),
+        Selector::UniversalSelector => true,
+        Selector::AttributeSelector { name, value } => match element.get_attribute(name) {
+            Some(v) => value.as_ref().map(|value| *value == v).unwrap_or(true),
+            None => false,
+        },
+        Selector::ComplexSelector(_) | Selector::UnknownSelector => false,
+    }
+}
+
+// [] 4.2.6. Mixin ParentNode | DOM Standard
+// https://dom.spec.whatwg.org/#dom-parentnode-queryselector
+// ----- Cited From Reference -----
+// The querySelector(selectors) method steps are to return the first result of running scope-match a selectors string selectors against this, if the result is not an empty list; otherwise null.
+// --------------------------------
+// 今のところ type, .class, #id の単純セレクタ 1 つだけ。読めないセレクタは例外を投げる代わりに None を返す
+pub fn query_selector(root: &Rc<RefCell<Node>>, selector: &str) -> Option<Rc<RefCell<Node>>> {
+    let selector = CssParser::new(CssTokenizer::new(selector.to_string())).parse_selector()?;
+    dom_iter(root).find(|node| selector_matches(&selector, node))
+}
+
+// [] 4.2.6 The style element | HTML Standard
+// https://html.spec.whatwg.org/multipage/semantics.html#the-style-element
+// ----- Cited From Reference -----
+// The child text content of a style element must be that of a conformant style sheet.
+// --------------------------------
+// 文書中のすべての style 要素の child text content を document order でつなげて、CSS パーサに渡せる形にする
+// head の外にある style 要素も拾う。子の要素 (本来は存在しないはず) の中身は無視する
+pub fn extract_inline_styles(document: &Rc<RefCell<Node>>) -> String {
+    let mut css = String::new();
+    for style in get_elements_by_tag_name(document, "style") {
+        let mut child = style.borrow().first_child();
+        while let Some(c) = child {
+            if let NodeKind::Text(ref text) = c.borrow().kind {
+                css.push_str(text);
+            }
+            child = c.borrow().next_sibling();
+        }
+    }
+    css
+}
+
+// [] 4.6.5 The a element | HTML Standard
+// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-a-element
+// ----- Cited From Reference -----
+// If the a element has an href attribute, then it represents a hyperlink (a hypertext anchor) labeled by its contents.
+// --------------------------------
+// 文書中のすべての a 要素の href を document order で返す。href のない a はリンクではないので飛ばす
+pub fn collect_links(document: &Rc<RefCell<Node>>) -> Vec<String> {
+    get_elements_by_tag_name(document, "a")
+        .iter()
+        .filter_map(|a| a.borrow().get_element()?.get_attribute("href"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use crate::renderer::html::{parser::HtmlParser, token::HtmlTokenizer};
+
+    #[test]
+    fn test_get_element_by_id() {
+        let html = "<html><head></head><body><p id=\"a\">x</p><p id=\"b\">y</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let p = get_element_by_id(&document, "a").expect("failed to find an element with id a");
+        assert_eq!(Some(ElementKind::P), p.borrow().get_element_kind());
+        assert_eq!(
+            Some("a".to_string()),
+            p.borrow().get_element().expect("p should be an element").get_attribute("id")
+        );
+
+        let p = get_element_by_id(&document, "b").expect("failed to find an element with id b");
+        assert_eq!(
+            Some("b".to_string()),
+            p.borrow().get_element().expect("p should be an element").get_attribute("id")
+        );
+    }
+
+    #[test]
+    fn test_get_element_by_id_not_found() {
+        let html = "<html><head></head><body><p id=\"a\">x</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert!(get_element_by_id(&document, "z").is_none());
+    }
+
+    #[test]
+    fn test_get_elements_by_tag_name() {
+        let html = "<html><head></head><body><p id=\"a\">x</p><a>y</a><p id=\"b\">z</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let elements = get_elements_by_tag_name(&document, "p");
+        assert_eq!(2, elements.len());
+        assert_eq!(
+            Some("a".to_string()),
+            elements[0].borrow().get_element().expect("p should be an element").get_attribute("id")
+        );
+        assert_eq!(
+            Some("b".to_string()),
+            elements[1].borrow().get_element().expect("p should be an element").get_attribute("id")
+        );
+
+        assert!(get_elements_by_tag_name(&document, "div").is_empty());
+    }
+
+    #[test]
+    fn test_selector_matches() {
+        let html = "<html><head></head><body><p id=\"x\" class=\"a b\">a</p><div class=\"ab\">b</div></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        let div = &get_elements_by_tag_name(&document, "div")[0];
+
+        let type_selector = Selector::TypeSelector("p".to_string());
+        assert!(selector_matches(&type_selector, p));
+        assert!(!selector_matches(&type_selector, div));
+
+        let class_selector = Selector::ClassSelector("b".to_string());
+        assert!(selector_matches(&class_selector, p));
+        assert!(!selector_matches(&class_selector, div));
+
+        let id_selector = Selector::IdSelector("x".to_string());
+        assert!(selector_matches(&id_selector, p));
+        assert!(!selector_matches(&id_selector, div));
+
+        let attribute_selector = Selector::AttributeSelector { name: "id".to_string(), value: None };
+        assert!(selector_matches(&attribute_selector, p));
+        assert!(!selector_matches(&attribute_selector, div));
+        let attribute_selector = Selector::AttributeSelector { name: "class".to_string(), value: Some("ab".to_string()) };
+        assert!(!selector_matches(&attribute_selector, p));
+        assert!(selector_matches(&attribute_selector, div));
+
+        assert!(selector_matches(&Selector::UniversalSelector, p));
+        assert!(selector_matches(&Selector::UniversalSelector, div));
+        assert!(!selector_matches(&Selector::UnknownSelector, p));
+        assert!(!selector_matches(&Selector::UnknownSelector, div));
+
+        // 要素でないノードにはマッチしない
+        assert!(!selector_matches(&Selector::UniversalSelector, &document));
+    }
+
+    #[test]
+    fn test_class_selector_matches_one_of_classes() {
+        let html = "<html><head></head><body><p id=\"x\" class=\"a b c\">a</p><p id=\"y\" class=\"ab\">b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let x = get_element_by_id(&document, "x").expect("failed to find #x");
+        let y = get_element_by_id(&document, "y").expect("failed to find #y");
+
+        // class 属性全体との比較ではなく、空白区切りのどれか 1 つと一致すればよい
+        let selector = Selector::ClassSelector("b".to_string());
+        assert!(selector_matches(&selector, &x));
+        assert!(!selector_matches(&selector, &y));
+    }
+
+    #[test]
+    fn test_query_selector() {
+        let html = "<html><head></head><body><div class=\"a\">x</div><p id=\"b\" class=\"a\">y</p><p>z</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let p = query_selector(&document, "p").expect("failed to find p");
+        assert_eq!("y", p.borrow().text_content());
+
+        let a = query_selector(&document, ".a").expect("failed to find .a");
+        assert_eq!(Some(ElementKind::Div), a.borrow().get_element_kind());
+
+        let b = query_selector(&document, "#b").expect("failed to find #b");
+        assert_eq!(Some(ElementKind::P), b.borrow().get_element_kind());
+        assert_eq!("y", b.borrow().text_content());
+
+        assert!(query_selector(&document, "span").is_none());
+        assert!(query_selector(&document, "#c").is_none());
+        assert!(query_selector(&document, "..").is_none());
+        assert!(query_selector(&document, "$").is_none());
+        assert!(query_selector(&document, "/").is_none());
+    }
+
+    #[test]
+    fn test_extract_inline_styles() {
+        let html = "<html><head><style>p{color:red}</style></head><body></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert_eq!("p{color:red}", extract_inline_styles(&document));
+    }
+
+    #[test]
+    fn test_extract_inline_styles_multiple() {
+        let html = "<html><head><style>p{color:red}</style><style>a{color:blue}</style></head><body><p>x</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert_eq!("p{color:red}a{color:blue}", extract_inline_styles(&document));
+    }
+
+    #[test]
+    fn test_extract_inline_styles_none() {
+        let html = "<html><head></head><body><p>x</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert_eq!("", extract_inline_styles(&document));
+    }
+
+    #[test]
+    fn test_collect_links() {
+        let html = "<html><head></head><body><a href=\"/x\">x</a><p><a>no link</a></p><a href=\"https://example.com/\">y</a></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        assert_eq!(vec!["/x".to_string(), "https://example.com/".to_string()], collect_links(&document));
+    }
+
+    #[test]
+    fn test_collect_links_none() {
+        let html = "<html><head></head><body><a>no link</a></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        assert!(collect_links(&document).is_empty());
+    }
+}
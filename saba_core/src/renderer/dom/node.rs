@@ -1,6 +1,6 @@
-use core::{cell::RefCell, str::FromStr};
+use core::{cell::RefCell, fmt, str::FromStr};
 
-use alloc::{format, rc::{Rc, Weak}, string::String, vec::Vec};
+use alloc::{rc::{Rc, Weak}, string::{String, ToString}, vec, vec::Vec};
 
 use crate::renderer::html::html_tag_attribute::HtmlTagAttribute;
 
@@ -70,6 +70,104 @@ impl Node {
         self.next_sibling.as_ref().cloned()
     }
 
+    // [] 4.2.6. Mixin ParentNode | DOM Standard
+    // https://dom.spec.whatwg.org/#dom-parentnode-children
+    // ----- Cited From Reference -----
+    // The children getter steps are to return an HTMLCollection collection rooted at this matching only element children.
+    // --------------------------------
+    // 直接の子のうち要素だけを document order で返す
+    pub fn child_elements(&self) -> Vec<Rc<RefCell<Node>>> {
+        let mut elements = Vec::new();
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            if let NodeKind::Element(_) = c.borrow().kind {
+                elements.push(c.clone());
+            }
+            child = c.borrow().next_sibling();
+        }
+        elements
+    }
+
+    // [] 4.2.3 Mutation algorithms | DOM Standard
+    // https://dom.spec.whatwg.org/#concept-node-remove
+    // target が自分の子なら、兄弟と親とのつながりを切って true を返す。子でなければ何もせず false を返す
+    // 取り除いた target は親も兄弟も持たない状態になる。target 自身の子はそのまま
+    pub fn remove_child(&mut self, target: &Rc<RefCell<Node>>) -> bool {
+        let mut child = self.first_child();
+        loop {
+            match child {
+                Some(c) if Rc::ptr_eq(&c, target) => break,
+                Some(c) => child = c.borrow().next_sibling(),
+                None => return false,
+            }
+        }
+
+        let previous = target.borrow().previous_sibling().upgrade();
+        let next = target.borrow().next_sibling();
+
+        match &previous {
+            Some(p) => p.borrow_mut().set_next_sibling(next.clone()),
+            None => self.set_first_child(next.clone()),
+        }
+        let previous = match previous {
+            Some(p) => Rc::downgrade(&p),
+            None => Weak::new(),
+        };
+        match &next {
+            Some(n) => n.borrow_mut().set_previous_sibling(previous),
+            None => self.set_last_child(previous),
+        }
+
+        let mut target = target.borrow_mut();
+        target.set_parent(Weak::new());
+        target.set_previous_sibling(Weak::new());
+        target.set_next_sibling(None);
+        true
+    }
+
+    // [] 4.2.7. Mixin NonDocumentTypeChildNode | DOM Standard
+    // https://dom.spec.whatwg.org/#dom-nondocumenttypechildnode-nextelementsibling
+    // ----- Cited From Reference -----
+    // The nextElementSibling getter steps are to return the first following sibling that is an element; otherwise null.
+    // --------------------------------
+    pub fn next_element_sibling(&self) -> Option<Rc<RefCell<Node>>> {
+        let mut sibling = self.next_sibling();
+        while let Some(s) = sibling {
+            if let NodeKind::Element(_) = s.borrow().kind {
+                return Some(s.clone());
+            }
+            sibling = s.borrow().next_sibling();
+        }
+        None
+    }
+
+    // https://dom.spec.whatwg.org/#dom-nondocumenttypechildnode-previouselementsibling
+    // ----- Cited From Reference -----
+    // The previousElementSibling getter steps are to return the first preceding sibling that is an element; otherwise null.
+    // --------------------------------
+    pub fn previous_element_sibling(&self) -> Option<Rc<RefCell<Node>>> {
+        let mut sibling = self.previous_sibling().upgrade();
+        while let Some(s) = sibling {
+            if let NodeKind::Element(_) = s.borrow().kind {
+                return Some(s.clone());
+            }
+            sibling = s.borrow().previous_sibling().upgrade();
+        }
+        None
+    }
+
+    // 自分が Text ならその末尾に c を足して true を返す。Text でなければ何もせず false を返す
+    // node_kind() は clone を返すので、そっちに push しても木には反映されないことに注意
+    pub fn push_char_to_text(&mut self, c: char) -> bool {
+        match self.kind {
+            NodeKind::Text(ref mut s) => {
+                s.push(c);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_element(&self) -> Option<Element> {
         match &self.kind {
             NodeKind::Element(element) => Some(element.clone()),
@@ -79,10 +177,205 @@ impl Node {
 
     pub fn get_element_kind(&self) -> Option<ElementKind> {
         match &self.kind {
-            NodeKind::Element(e) => Some(e.kind),
+            NodeKind::Element(e) => Some(e.kind.clone()),
             _ => None
         }
     }
+
+    // [] 4.4 Interface Node | DOM Standard
+    // https://dom.spec.whatwg.org/#dom-node-textcontent
+    // 子孫の Text をドキュメント順に全部つなげたもの。空白だけの Text もそのまま含める
+    pub fn text_content(&self) -> String {
+        let mut content = String::new();
+        match &self.kind {
+            NodeKind::Text(text) => content.push_str(text),
+            // br は子を持たないので、改行を 1 つ出せば十分
+            NodeKind::Element(e) if e.kind() == ElementKind::Br => content.push('\n'),
+            _ => {}
+        }
+
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            content.push_str(&c.borrow().text_content());
+            child = c.borrow().next_sibling();
+        }
+
+        content
+    }
+
+    // [] 13.3 Serializing HTML fragments | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#serialising-html-fragments
+    // 自身と子孫を HTML 文字列に戻す。デバッグやテストのためのものなので、spec の細かい分岐はかなりサボっている
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+
+        match &self.kind {
+            NodeKind::Document => {}
+            NodeKind::Element(e) => {
+                html.push('<');
+                html.push_str(&e.tag_name());
+                for attr in e.attributes() {
+                    html.push(' ');
+                    html.push_str(&attr.name());
+                    html.push_str("=\"");
+                    html.push_str(&escape_html(&attr.value(), true));
+                    html.push('"');
+                }
+                html.push('>');
+
+                // void 要素は子を持たず、終了タグも書かない
+                if e.kind().is_void() {
+                    return html;
+                }
+            }
+            NodeKind::Text(text) => html.push_str(&escape_html(text, false)),
+        }
+
+        let is_raw_text = matches!(self.get_element_kind(), Some(ElementKind::Style) | Some(ElementKind::Script));
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            match c.borrow().kind {
+                // style と script の中身は文字参照を解釈しないので、エスケープせずにそのまま書く
+                NodeKind::Text(ref text) if is_raw_text => html.push_str(text),
+                _ => html.push_str(&c.borrow().to_html()),
+            }
+            child = c.borrow().next_sibling();
+        }
+
+        if let NodeKind::Element(e) = &self.kind {
+            html.push_str("</");
+            html.push_str(&e.tag_name());
+            html.push('>');
+        }
+
+        html
+    }
+}
+
+// [] 13.3 Serializing HTML fragments | HTML Standard
+// https://html.spec.whatwg.org/multipage/parsing.html#escapingString
+// ----- Cited From Reference -----
+// Replace any occurrence of the "&" character by the string "&amp;".
+// Replace any occurrences of the U+00A0 NO-BREAK SPACE character by the string "&nbsp;".
+// Replace any occurrences of the "<" character by the string "&lt;".
+// Replace any occurrences of the ">" character by the string "&gt;".
+// If the algorithm was invoked in the attribute mode, then replace any occurrences of the """ character by the string "&quot;".
+// --------------------------------
+// tokenizer が文字参照をデコードしているので、パースしたものを書き戻すときはエスケープしておかないと壊れる
+fn escape_html(s: &str, attribute_mode: bool) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '\u{00A0}' => escaped.push_str("&nbsp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute_mode => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// first_child / next_sibling をたどる深さ優先の iterator
+// 次に訪れるノードを Rc で stack に持っておき、RefCell の borrow は next() の中だけで手放す
+// こうしておけば、iteration の途中で呼び出し側がノードを borrow_mut してもパニックしない
+#[derive(Debug, Clone)]
+pub struct NodeIterator {
+    root: Rc<RefCell<Node>>,
+    stack: Vec<Rc<RefCell<Node>>>,
+}
+
+impl NodeIterator {
+    pub fn new(root: &Rc<RefCell<Node>>) -> Self {
+        Self { root: Rc::clone(root), stack: vec![Rc::clone(root)] }
+    }
+}
+
+impl Iterator for NodeIterator {
+    type Item = Rc<RefCell<Node>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        {
+            let n = node.borrow();
+            // root の兄弟は root の subtree ではないのでたどらない
+            if !Rc::ptr_eq(&node, &self.root) {
+                if let Some(sibling) = n.next_sibling() {
+                    self.stack.push(sibling);
+                }
+            }
+            // 子を後に積むので、兄弟より先に子を訪れることになる
+            if let Some(child) = n.first_child() {
+                self.stack.push(child);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+// root 自身を含む subtree のノードを document order で返す
+pub fn dom_iter(root: &Rc<RefCell<Node>>) -> NodeIterator {
+    NodeIterator::new(root)
+}
+
+// root 自身を含む subtree のノード数
+pub fn node_count(root: &Rc<RefCell<Node>>) -> usize {
+    dom_iter(root).count()
+}
+
+// [] 4.2.3 Mutation algorithms | DOM Standard
+// https://dom.spec.whatwg.org/#concept-node-append
+// parent の子の末尾に child を加える。last_child を持っているので、兄弟をたどらずに O(1) でつなげられる
+// ここで mutate したいので Node の Fields は RefCell で包まないといけない。Rc::get_mut は参照が 1 つとは限らないので使えない
+pub fn append_child(parent: &Rc<RefCell<Node>>, child: &Rc<RefCell<Node>>) {
+    let last_child = parent.borrow().last_child().upgrade();
+    match last_child {
+        Some(last) => {
+            last.borrow_mut().set_next_sibling(Some(Rc::clone(child)));
+            child.borrow_mut().set_previous_sibling(Rc::downgrade(&last));
+        }
+        None => parent.borrow_mut().set_first_child(Some(Rc::clone(child))),
+    }
+
+    parent.borrow_mut().set_last_child(Rc::downgrade(child));
+    child.borrow_mut().set_parent(Rc::downgrade(parent));
+}
+
+// [] 4.2.3 Mutation algorithms | DOM Standard
+// https://dom.spec.whatwg.org/#concept-node-insert
+// new_node を parent の子として reference の直前に入れる。reference が None なら append_child と同じ
+// reference が parent の子でなければ何もせず false を返す (spec では NotFoundError を投げる)
+// new_node はどこにもつながっていないものとする
+pub fn insert_before(parent: &Rc<RefCell<Node>>, new_node: &Rc<RefCell<Node>>, reference: Option<&Rc<RefCell<Node>>>) -> bool {
+    let reference = match reference {
+        Some(r) => r,
+        None => {
+            append_child(parent, new_node);
+            return true;
+        }
+    };
+
+    match reference.borrow().parent().upgrade() {
+        Some(p) if Rc::ptr_eq(&p, parent) => {}
+        _ => return false,
+    }
+
+    let previous = reference.borrow().previous_sibling().upgrade();
+    match &previous {
+        Some(p) => {
+            p.borrow_mut().set_next_sibling(Some(Rc::clone(new_node)));
+            new_node.borrow_mut().set_previous_sibling(Rc::downgrade(p));
+        }
+        None => parent.borrow_mut().set_first_child(Some(Rc::clone(new_node))),
+    }
+
+    new_node.borrow_mut().set_next_sibling(Some(Rc::clone(reference)));
+    reference.borrow_mut().set_previous_sibling(Rc::downgrade(new_node));
+    new_node.borrow_mut().set_parent(Rc::downgrade(parent));
+    true
 }
 
 impl PartialEq for Node {
@@ -91,6 +384,34 @@ impl PartialEq for Node {
     }
 }
 
+// a と b を根とする部分木が同じ形をしているか。Node の == と違って子孫までたどり、Text は中身も比べる
+// 要素の属性は比べない
+pub fn dom_equal(a: &Rc<RefCell<Node>>, b: &Rc<RefCell<Node>>) -> bool {
+    let same_node = match (&a.borrow().kind, &b.borrow().kind) {
+        (NodeKind::Text(t1), NodeKind::Text(t2)) => t1 == t2,
+        (k1, k2) => k1 == k2,
+    };
+    if !same_node {
+        return false;
+    }
+
+    let mut child_a = a.borrow().first_child();
+    let mut child_b = b.borrow().first_child();
+    loop {
+        match (child_a, child_b) {
+            (Some(ca), Some(cb)) => {
+                if !dom_equal(&ca, &cb) {
+                    return false;
+                }
+                child_a = ca.borrow().next_sibling();
+                child_b = cb.borrow().next_sibling();
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
 // [] 4.2. Node tree | DOM Standard
 // https://dom.spec.whatwg.org/#node-trees
 // ----- Cited From Reference -----
@@ -178,39 +499,173 @@ pub struct Element {
 
 impl Element {
     pub fn new(kind: &str, attributes: Vec<HtmlTagAttribute>) -> Self {
-        Element { kind: ElementKind::from_str(kind).expect("failed to convert string to ElementKind"), attributes: attributes }
+        Element { kind: ElementKind::from(kind), attributes: attributes }
     }
 
     pub fn kind(&self) -> ElementKind {
-        self.kind
+        self.kind.clone()
+    }
+
+    // 小文字のタグ名。Unknown なら持っているタグ名をそのまま返す
+    pub fn tag_name(&self) -> String {
+        self.kind.to_string()
+    }
+
+    pub fn attributes(&self) -> &[HtmlTagAttribute] {
+        &self.attributes
+    }
+
+    // NodeKind の == は要素の種類しか見ないので、属性まで (順番も含めて) 比べたいときに使う
+    pub fn eq_with_attributes(&self, other: &Element) -> bool {
+        self.kind == other.kind && self.attributes == other.attributes
+    }
+
+    // 属性名は tokenizer の時点で小文字になっているので、そのまま完全一致で比較する
+    // 同じ名前の属性が複数あるときは最初のものを返す
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name() == name)
+            .map(|attr| attr.value())
+    }
+
+    // id 属性の値。よく使うので get_attribute("id") のショートカットとして用意しておく
+    pub fn id(&self) -> Option<String> {
+        self.get_attribute("id")
+    }
+
+    // [] 3.2.6 Global attributes | HTML Standard
+    // https://html.spec.whatwg.org/multipage/dom.html#classes
+    // ----- Cited From Reference -----
+    // the value must be a set of space-separated tokens
+    // --------------------------------
+    // 重複は取り除かずにそのまま返す
+    pub fn class_list(&self) -> Vec<String> {
+        match self.get_attribute("class") {
+            Some(classes) => classes.split_ascii_whitespace().map(|c| c.to_string()).collect(),
+            None => Vec::new(),
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+// 知らないタグ名は Unknown にタグ名をそのまま持たせる。このために Copy は derive できない
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ElementKind {
     Html,
     Head,
+    Title,
     Style,
     Script,
     Body,
     P,
     A,
+    Textarea,
+    Div,
+    Span,
+    Ul,
+    Ol,
+    Li,
+    H1,
+    H2,
+    H3,
+    H4,
+    H5,
+    H6,
+    Img,
+    Br,
+    Unknown(String),
+}
+
+impl ElementKind {
+    // [] 13.1.2 Elements | HTML Standard
+    // https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+    // ----- Cited From Reference -----
+    // Void elements: area, base, br, col, embed, hr, img, input, link, meta, source, track, wbr
+    // --------------------------------
+    // basefont と bgsound は廃止された要素だが、終了タグを持たないのでここに含めておく
+    pub fn is_void(&self) -> bool {
+        match self {
+            ElementKind::Img | ElementKind::Br => true,
+            ElementKind::Unknown(name) => matches!(
+                name.as_str(),
+                "area" | "base" | "basefont" | "bgsound" | "col" | "embed" | "hr" | "input" | "link" | "meta"
+                    | "source" | "track" | "wbr"
+            ),
+            _ => false,
+        }
+    }
+}
+
+// タグ名の文字列に戻す。Unknown は元のタグ名をそのまま返す
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ElementKind::Html => "html",
+            ElementKind::Head => "head",
+            ElementKind::Title => "title",
+            ElementKind::Style => "style",
+            ElementKind::Script => "script",
+            ElementKind::Body => "body",
+            ElementKind::P => "p",
+            ElementKind::A => "a",
+            ElementKind::Textarea => "textarea",
+            ElementKind::Div => "div",
+            ElementKind::Span => "span",
+            ElementKind::Ul => "ul",
+            ElementKind::Ol => "ol",
+            ElementKind::Li => "li",
+            ElementKind::H1 => "h1",
+            ElementKind::H2 => "h2",
+            ElementKind::H3 => "h3",
+            ElementKind::H4 => "h4",
+            ElementKind::H5 => "h5",
+            ElementKind::H6 => "h6",
+            ElementKind::Img => "img",
+            ElementKind::Br => "br",
+            ElementKind::Unknown(name) => name,
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// 知らないタグ名も Unknown になるので、タグ名からの変換は失敗しない
+impl From<&str> for ElementKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "html" => Self::Html,
+            "head" => Self::Head,
+            "title" => Self::Title,
+            "style" => Self::Style,
+            "script" => Self::Script,
+            "body" => Self::Body,
+            "p" => Self::P,
+            "a" => Self::A,
+            "textarea" => Self::Textarea,
+            "div" => Self::Div,
+            "span" => Self::Span,
+            "ul" => Self::Ul,
+            "ol" => Self::Ol,
+            "li" => Self::Li,
+            "h1" => Self::H1,
+            "h2" => Self::H2,
+            "h3" => Self::H3,
+            "h4" => Self::H4,
+            "h5" => Self::H5,
+            "h6" => Self::H6,
+            "img" => Self::Img,
+            "br" => Self::Br,
+            // 知らない要素でパニックしないように、Unknown として受け付ける
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
 }
 
+// 失敗しないが、文字列からの変換として str::parse でも使えるようにしておく
 impl FromStr for ElementKind {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "html" => Ok(Self::Html),
-            "head" => Ok(Self::Head),
-            "style" => Ok(Self::Style),
-            "script" => Ok(Self::Style),
-            "body" => Ok(Self::Body),
-            "p" => Ok(Self::P),
-            "a" => Ok(Self::A),
-            _ => Err(format!("unimplemented element name: {:?}", s)),
-        }
+        Ok(Self::from(s))
     }
 }
 
@@ -227,4 +682,504 @@ impl Window {
     pub fn document(&self) -> Rc<RefCell<Node>> {
         Rc::clone(&self.document)
     }
+
+    // document 直下の html 要素の子から最初の body 要素を探す
+    pub fn body(&self) -> Option<Rc<RefCell<Node>>> {
+        self.html_child(ElementKind::Body)
+    }
+
+    // https://html.spec.whatwg.org/multipage/dom.html#document.title
+    // head の中の最初の title 要素のテキストを返す。title がなければ空文字列
+    pub fn title(&self) -> String {
+        let head = match self.html_child(ElementKind::Head) {
+            Some(head) => head,
+            None => return String::new(),
+        };
+        let title = head
+            .borrow()
+            .child_elements()
+            .into_iter()
+            .find(|n| n.borrow().get_element_kind() == Some(ElementKind::Title));
+        match title {
+            Some(title) => title.borrow().text_content(),
+            None => String::new(),
+        }
+    }
+
+    fn html_child(&self, kind: ElementKind) -> Option<Rc<RefCell<Node>>> {
+        let html = self
+            .document
+            .borrow()
+            .child_elements()
+            .into_iter()
+            .find(|n| n.borrow().get_element_kind() == Some(ElementKind::Html))?;
+        let child = html
+            .borrow()
+            .child_elements()
+            .into_iter()
+            .find(|n| n.borrow().get_element_kind().as_ref() == Some(&kind));
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::dom::api::get_elements_by_tag_name;
+    use crate::renderer::html::{html_tag_attribute::AttributeField, parser::HtmlParser, token::HtmlTokenizer};
+
+    fn attribute(name: &str, value: &str) -> HtmlTagAttribute {
+        let mut attr = HtmlTagAttribute::new();
+        for c in name.chars() {
+            attr.add_char(c, AttributeField::Name);
+        }
+        for c in value.chars() {
+            attr.add_char(c, AttributeField::Value);
+        }
+        attr
+    }
+
+    #[test]
+    fn test_element_kind_from_str() {
+        let expected = vec![
+            ("div", ElementKind::Div),
+            ("span", ElementKind::Span),
+            ("ul", ElementKind::Ul),
+            ("ol", ElementKind::Ol),
+            ("li", ElementKind::Li),
+            ("h1", ElementKind::H1),
+            ("h2", ElementKind::H2),
+            ("h3", ElementKind::H3),
+            ("h4", ElementKind::H4),
+            ("h5", ElementKind::H5),
+            ("h6", ElementKind::H6),
+            ("img", ElementKind::Img),
+        ];
+        for (tag, kind) in expected {
+            assert_eq!(Ok(kind), ElementKind::from_str(tag));
+        }
+    }
+
+    #[test]
+    fn test_unknown_element_kind() {
+        assert_eq!(
+            Ok(ElementKind::Unknown("foo".to_string())),
+            ElementKind::from_str("foo")
+        );
+
+        // 知らないタグ名でも Element を作ってもパニックしない
+        let element = Element::new("foo", Vec::new());
+        assert_eq!(ElementKind::Unknown("foo".to_string()), element.kind());
+    }
+
+    #[test]
+    fn test_script_element_kind() {
+        assert_eq!(Ok(ElementKind::Script), ElementKind::from_str("script"));
+        assert_eq!(Ok(ElementKind::Style), ElementKind::from_str("style"));
+    }
+
+    #[test]
+    fn test_is_void() {
+        assert!(ElementKind::Img.is_void());
+        assert!(ElementKind::Br.is_void());
+        assert!(ElementKind::Unknown("meta".to_string()).is_void());
+        assert!(!ElementKind::P.is_void());
+        assert!(!ElementKind::Unknown("foo".to_string()).is_void());
+    }
+
+    #[test]
+    fn test_get_attribute() {
+        let element = Element::new("a", vec![attribute("href", "/x"), attribute("href", "/y")]);
+        assert_eq!(Some("/x".to_string()), element.get_attribute("href"));
+        assert_eq!(None, element.get_attribute("class"));
+        assert_eq!(2, element.attributes().len());
+        assert_eq!("href".to_string(), element.attributes()[1].name());
+        assert_eq!("/y".to_string(), element.attributes()[1].value());
+    }
+
+    #[test]
+    fn test_tag_name() {
+        let element = Element::new("p", Vec::new());
+        assert_eq!("p", element.tag_name());
+
+        let element = Element::new("section", Vec::new());
+        assert_eq!("section", element.tag_name());
+    }
+
+    #[test]
+    fn test_eq_with_attributes() {
+        let html = "<html><head></head><body><a href=\"/x\">a</a><a href=\"/y\">b</a><a href=\"/x\">c</a></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+        let a = get_elements_by_tag_name(&document, "a");
+        assert_eq!(3, a.len());
+
+        // Node の == は属性を見ない
+        assert!(*a[0].borrow() == *a[1].borrow());
+
+        let x1 = a[0].borrow().get_element().expect("a should be an element");
+        let y = a[1].borrow().get_element().expect("a should be an element");
+        let x2 = a[2].borrow().get_element().expect("a should be an element");
+        assert!(!x1.eq_with_attributes(&y));
+        assert!(x1.eq_with_attributes(&x2));
+        assert!(x1.eq_with_attributes(&Element::new("a", vec![attribute("href", "/x")])));
+        assert!(!x1.eq_with_attributes(&Element::new("p", vec![attribute("href", "/x")])));
+    }
+
+    #[test]
+    fn test_window_body() {
+        let html = "<html><head></head><body><p>a</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let body = window.borrow().body().expect("body should exist");
+        assert_eq!(Some(ElementKind::Body), body.borrow().get_element_kind());
+        assert_eq!(
+            Some(ElementKind::P),
+            body.borrow().first_child().expect("p should exist").borrow().get_element_kind()
+        );
+
+        assert!(Window::new().body().is_none());
+    }
+
+    #[test]
+    fn test_window_title() {
+        let html = "<head><title>Hello</title></head>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        assert_eq!("Hello", window.borrow().title());
+
+        let html = "<html><head></head><body><p>title</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        assert_eq!("", window.borrow().title());
+    }
+
+    #[test]
+    fn test_id() {
+        let element = Element::new("p", vec![attribute("class", "a"), attribute("id", "x")]);
+        assert_eq!(Some("x".to_string()), element.id());
+
+        let element = Element::new("p", vec![attribute("class", "a")]);
+        assert_eq!(None, element.id());
+    }
+
+    #[test]
+    fn test_class_list() {
+        let element = Element::new("p", vec![attribute("class", "a b  a")]);
+        assert_eq!(vec!["a".to_string(), "b".to_string(), "a".to_string()], element.class_list());
+
+        let element = Element::new("p", Vec::new());
+        assert!(element.class_list().is_empty());
+    }
+
+    #[test]
+    fn test_dom_iter() {
+        let html = "<html><head></head><body>text</body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let expected = [
+            Node::new(NodeKind::Document),
+            Node::new(NodeKind::Element(Element::new("html", Vec::new()))),
+            Node::new(NodeKind::Element(Element::new("head", Vec::new()))),
+            Node::new(NodeKind::Element(Element::new("body", Vec::new()))),
+            Node::new(NodeKind::Text("text".to_string())),
+        ];
+        let nodes: Vec<Rc<RefCell<Node>>> = dom_iter(&document).collect();
+        assert_eq!(expected.len(), nodes.len());
+        for (e, n) in expected.iter().zip(nodes.iter()) {
+            assert_eq!(e, &*n.borrow());
+        }
+    }
+
+    #[test]
+    fn test_child_elements() {
+        let html = "<html><head></head><body>text<p>x</p>more<a></a></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = get_elements_by_tag_name(&document, "body");
+        assert_eq!(1, body.len());
+        let children = body[0].borrow().child_elements();
+        assert_eq!(2, children.len());
+        assert_eq!(Some(ElementKind::P), children[0].borrow().get_element_kind());
+        assert_eq!(Some(ElementKind::A), children[1].borrow().get_element_kind());
+
+        let p = get_elements_by_tag_name(&document, "p");
+        assert!(p[0].borrow().child_elements().is_empty());
+    }
+
+    #[test]
+    fn test_element_siblings() {
+        let html = "<html><head></head><body><p>a</p>text<a>b</a></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        let a = &get_elements_by_tag_name(&document, "a")[0];
+
+        let next = p.borrow().next_element_sibling().expect("failed to get a next element sibling of p");
+        assert!(Rc::ptr_eq(a, &next));
+        let previous = a.borrow().previous_element_sibling().expect("failed to get a previous element sibling of a");
+        assert!(Rc::ptr_eq(p, &previous));
+
+        // 端では None
+        assert!(p.borrow().previous_element_sibling().is_none());
+        assert!(a.borrow().next_element_sibling().is_none());
+    }
+
+    #[test]
+    fn test_node_count() {
+        let html = "<html><head></head><body>x</body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        // Document, html, head, body, "x"
+        assert_eq!(5, node_count(&document));
+
+        let body = get_elements_by_tag_name(&document, "body");
+        assert_eq!(1, body.len());
+        assert_eq!(2, node_count(&body[0]));
+    }
+
+    #[test]
+    fn test_dom_iter_subtree() {
+        let html = "<html><head></head><body><p>a</p><p>b</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .last_child()
+            .upgrade()
+            .expect("failed to get a last child of html");
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+
+        // subtree の根の兄弟はたどらない
+        let nodes: Vec<Rc<RefCell<Node>>> = dom_iter(&p).collect();
+        assert_eq!(2, nodes.len());
+        assert!(Rc::ptr_eq(&p, &nodes[0]));
+        assert_eq!(Node::new(NodeKind::Text("a".to_string())), *nodes[1].borrow());
+    }
+
+    #[test]
+    fn test_to_html() {
+        let html = "<html><head></head><body><p class=\"x\">hi</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html.clone());
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert_eq!(html, document.borrow().to_html());
+    }
+
+    #[test]
+    fn test_to_html_void_and_escape() {
+        let html = "<html><head></head><body><p>a&lt;b&amp;c</p><br><img src=\"/x\"></body></html>".to_string();
+        let t = HtmlTokenizer::new(html.clone());
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert_eq!(html, document.borrow().to_html());
+    }
+
+    #[test]
+    fn test_text_content() {
+        let html = "<html><head></head><body><p>Hello <a>world</a></p><div><span>!</span></div></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        assert_eq!("Hello world!", document.borrow().text_content());
+
+        let body = get_elements_by_tag_name(&document, "body");
+        assert_eq!(1, body.len());
+        assert_eq!("Hello world!", body[0].borrow().text_content());
+        let a = get_elements_by_tag_name(&document, "a");
+        assert_eq!(1, a.len());
+        assert_eq!("world", a[0].borrow().text_content());
+    }
+
+    #[test]
+    fn test_text_content_br() {
+        let html = "<html><head></head><body><p>a<br>b</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let p = get_elements_by_tag_name(&document, "p");
+        assert_eq!(1, p.len());
+        assert_eq!("a\nb", p[0].borrow().text_content());
+    }
+
+    #[test]
+    fn test_append_child() {
+        let parent = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("ul", Vec::new())))));
+        let count = 1000;
+        let mut children = Vec::new();
+        for _ in 0..count {
+            let child = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("li", Vec::new())))));
+            append_child(&parent, &child);
+            children.push(child);
+        }
+
+        assert!(Rc::ptr_eq(&children[0], &parent.borrow().first_child().expect("failed to get a first child")));
+        assert!(Rc::ptr_eq(&children[count - 1], &parent.borrow().last_child().upgrade().expect("failed to get a last child")));
+        for (i, child) in children.iter().enumerate() {
+            let c = child.borrow();
+            assert!(Rc::ptr_eq(&parent, &c.parent().upgrade().expect("failed to get a parent")));
+            match c.previous_sibling().upgrade() {
+                Some(prev) => assert!(Rc::ptr_eq(&children[i - 1], &prev)),
+                None => assert_eq!(0, i),
+            }
+            match c.next_sibling() {
+                Some(next) => assert!(Rc::ptr_eq(&children[i + 1], &next)),
+                None => assert_eq!(count - 1, i),
+            }
+        }
+    }
+
+    fn li_list(count: usize) -> (Rc<RefCell<Node>>, Vec<Rc<RefCell<Node>>>) {
+        let parent = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("ul", Vec::new())))));
+        let mut children = Vec::new();
+        for _ in 0..count {
+            let child = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("li", Vec::new())))));
+            append_child(&parent, &child);
+            children.push(child);
+        }
+        (parent, children)
+    }
+
+    #[test]
+    fn test_remove_child() {
+        let (parent, children) = li_list(3);
+
+        assert!(parent.borrow_mut().remove_child(&children[1]));
+
+        // 前後の兄弟どうしがつながる
+        let next = children[0].borrow().next_sibling().expect("failed to get a next sibling");
+        assert!(Rc::ptr_eq(&children[2], &next));
+        let previous = children[2].borrow().previous_sibling().upgrade().expect("failed to get a previous sibling");
+        assert!(Rc::ptr_eq(&children[0], &previous));
+
+        // 取り除いたノードはどこにもつながっていない
+        let removed = children[1].borrow();
+        assert!(removed.parent().upgrade().is_none());
+        assert!(removed.previous_sibling().upgrade().is_none());
+        assert!(removed.next_sibling().is_none());
+    }
+
+    #[test]
+    fn test_remove_first_and_last_child() {
+        let (parent, children) = li_list(3);
+
+        assert!(parent.borrow_mut().remove_child(&children[0]));
+        assert!(Rc::ptr_eq(&children[1], &parent.borrow().first_child().expect("failed to get a first child")));
+        assert!(children[1].borrow().previous_sibling().upgrade().is_none());
+
+        assert!(parent.borrow_mut().remove_child(&children[2]));
+        assert!(Rc::ptr_eq(&children[1], &parent.borrow().last_child().upgrade().expect("failed to get a last child")));
+        assert!(children[1].borrow().next_sibling().is_none());
+
+        // もう子ではないので見つからない
+        assert!(!parent.borrow_mut().remove_child(&children[0]));
+
+        assert!(parent.borrow_mut().remove_child(&children[1]));
+        assert!(parent.borrow().first_child().is_none());
+        assert!(parent.borrow().last_child().upgrade().is_none());
+    }
+
+    fn assert_children(parent: &Rc<RefCell<Node>>, expected: &[&Rc<RefCell<Node>>]) {
+        let mut child = parent.borrow().first_child();
+        let mut previous: Option<Rc<RefCell<Node>>> = None;
+        for e in expected {
+            let c = child.expect("failed to get a child");
+            assert!(Rc::ptr_eq(e, &c));
+            assert!(Rc::ptr_eq(parent, &c.borrow().parent().upgrade().expect("failed to get a parent")));
+            match (&previous, c.borrow().previous_sibling().upgrade()) {
+                (Some(p), Some(ps)) => assert!(Rc::ptr_eq(p, &ps)),
+                (None, None) => {}
+                _ => panic!("previous sibling is broken"),
+            }
+            child = c.borrow().next_sibling();
+            previous = Some(c);
+        }
+        assert!(child.is_none());
+        let last = parent.borrow().last_child().upgrade();
+        match (previous, last) {
+            (Some(p), Some(l)) => assert!(Rc::ptr_eq(&p, &l)),
+            (None, None) => {}
+            _ => panic!("last child is broken"),
+        }
+    }
+
+    #[test]
+    fn test_insert_before() {
+        let (parent, children) = li_list(2);
+        let new_node = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("li", Vec::new())))));
+
+        assert!(insert_before(&parent, &new_node, Some(&children[1])));
+        assert_children(&parent, &[&children[0], &new_node, &children[1]]);
+    }
+
+    #[test]
+    fn test_insert_before_first_and_append() {
+        let (parent, children) = li_list(1);
+        let first = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("li", Vec::new())))));
+        let last = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("li", Vec::new())))));
+
+        assert!(insert_before(&parent, &first, Some(&children[0])));
+        assert!(insert_before(&parent, &last, None));
+        assert_children(&parent, &[&first, &children[0], &last]);
+
+        // 子でないノードの前には入れられない
+        let (other, _) = li_list(0);
+        let orphan = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new("li", Vec::new())))));
+        assert!(!insert_before(&other, &orphan, Some(&children[0])));
+        assert_children(&other, &[]);
+    }
+
+    fn new_node(kind: NodeKind) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node::new(kind)))
+    }
+
+    #[test]
+    fn test_dom_equal() {
+        let html = "<html><head></head><body>x</body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let expected = new_node(NodeKind::Document);
+        let html = new_node(NodeKind::Element(Element::new("html", Vec::new())));
+        let head = new_node(NodeKind::Element(Element::new("head", Vec::new())));
+        let body = new_node(NodeKind::Element(Element::new("body", Vec::new())));
+        let text = new_node(NodeKind::Text("x".to_string()));
+        append_child(&expected, &html);
+        append_child(&html, &head);
+        append_child(&html, &body);
+        append_child(&body, &text);
+        assert!(dom_equal(&expected, &document));
+
+        // Text の中身が違う
+        text.borrow_mut().push_char_to_text('y');
+        assert!(!dom_equal(&expected, &document));
+
+        // 根が同じでも子の数が違う
+        let different = new_node(NodeKind::Document);
+        append_child(&different, &new_node(NodeKind::Element(Element::new("html", Vec::new()))));
+        assert!(*different.borrow() == *document.borrow());
+        assert!(!dom_equal(&different, &document));
+    }
 }
@@ -1,8 +1,8 @@
-use core::{cell::RefCell, str::FromStr};
+use core::cell::RefCell;
 
 use alloc::{rc::Rc, string::ToString, vec::Vec};
 
-use crate::renderer::dom::node::{Element, ElementKind, Node, NodeKind, Window};
+use crate::renderer::dom::node::{append_child, Element, ElementKind, Node, NodeKind, Window};
 
 use super::{html_tag_attribute::HtmlTagAttribute, token::{HtmlToken, HtmlTokenizer}};
 
@@ -29,6 +29,23 @@ pub enum InsertionMode {
     AfterAfterBody,
 }
 
+// [] 13.2.6.4.7 The "in body" insertion mode | HTML Standard
+// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
+// ----- Cited From Reference -----
+// A start tag whose tag name is one of: "address", "article", "aside", "blockquote", "center", "details", "dialog", "dir", "div", "dl", "fieldset", "figcaption", "figure", "footer", "header", "hgroup", "main", "menu", "nav", "ol", "p", "search", "section", "summary", "ul"
+// If the stack of open elements has a p element in button scope, then close a p element.
+// --------------------------------
+// h1-h6, pre, listing, form, hr なども p を閉じるので、まとめてここで判定する
+fn closes_p_element(tag: &str) -> bool {
+    matches!(
+        tag,
+        "address" | "article" | "aside" | "blockquote" | "center" | "details" | "dialog" | "dir" | "div" | "dl"
+            | "fieldset" | "figcaption" | "figure" | "footer" | "header" | "hgroup" | "main" | "menu" | "nav"
+            | "ol" | "p" | "search" | "section" | "summary" | "ul" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+            | "pre" | "listing" | "form" | "hr"
+    )
+}
+
 impl HtmlParser {
     pub fn new(tokenizer: HtmlTokenizer) -> Self {
         Self { window: Rc::new(RefCell::new(Window::new())), current_mode: InsertionMode::Initial, original_mode: InsertionMode::Initial, stack_of_open_elements: Vec::new(), tokenizer }
@@ -61,7 +78,7 @@ impl HtmlParser {
                         },
                         Some(HtmlToken::StartTag { ref tag, self_closing, ref attributes }) => {
                             if tag == "html" {
-                                self.insert_element(tag, attributes.to_vec());
+                                self.insert_element(tag, attributes.to_vec(), self_closing);
                                 self.current_mode = InsertionMode::BeforeHead;
                                 token = self.tokenizer.next();
                                 continue;
@@ -72,7 +89,7 @@ impl HtmlParser {
                         },
                         _ => {}
                     }
-                    self.insert_element("html", Vec::new());
+                    self.insert_element("html", Vec::new(), false);
                     self.current_mode = InsertionMode::BeforeHead;
                     continue;
                 },
@@ -86,7 +103,7 @@ impl HtmlParser {
                         },
                         Some(HtmlToken::StartTag { ref tag, self_closing, ref attributes }) => {
                             if tag == "head" {
-                                self.insert_element(tag, attributes.to_vec());
+                                self.insert_element(tag, attributes.to_vec(), self_closing);
                                 self.current_mode = InsertionMode::InHead;
                                 token = self.tokenizer.next();
                                 continue;
@@ -97,7 +114,7 @@ impl HtmlParser {
                         },
                         _ => {}
                     }
-                    self.insert_element("head", Vec::new());
+                    self.insert_element("head", Vec::new(), false);
                     self.current_mode = InsertionMode::InHead;
                     continue;
                 },
@@ -111,25 +128,34 @@ impl HtmlParser {
                             }
                         },
                         Some(HtmlToken::StartTag { ref tag, self_closing, ref attributes }) => {
-                            if tag == "style" || tag == "script" {
-                                self.insert_element(tag, attributes.to_vec());
+                            // title は RCDATA、style と script は raw text だが、このブラウザではどちらも Text モードで扱う
+                            if tag == "style" || tag == "script" || tag == "title" {
+                                self.insert_element(tag, attributes.to_vec(), self_closing);
                                 self.original_mode = self.current_mode;
                                 self.current_mode = InsertionMode::Text;
                                 token = self.tokenizer.next();
                                 continue;
                             }
 
-                            // ここがないと head が省略されている html document で無限ループが出るらしい
-                            if tag == "body" {
-                                self.pop_until(ElementKind::Head);
-                                self.current_mode = InsertionMode::AfterHead;
-                                continue;
-                            }
-                            if let Ok(_element_kind) = ElementKind::from_str(tag) {
-                                self.pop_until(ElementKind::Head);
-                                self.current_mode = InsertionMode::AfterHead;
+                            // [] 13.2.6.4.4 The "in head" insertion mode | HTML Standard
+                            // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inhead
+                            // ----- Cited From Reference -----
+                            // A start tag whose tag name is one of: "base", "basefont", "bgsound", "link"
+                            // Insert an HTML element for the token. Immediately pop the current node off the stack of open elements.
+                            // --------------------------------
+                            // meta も同様。これがないと、知らない要素として head が閉じられてしまう
+                            // これらは void 要素なので、insert_element が stack に積まないことで pop したのと同じになる
+                            if tag == "base" || tag == "basefont" || tag == "bgsound" || tag == "link" || tag == "meta" {
+                                self.insert_element(tag, attributes.to_vec(), self_closing);
+                                token = self.tokenizer.next();
                                 continue;
                             }
+
+                            // body や知らない要素のように head に入らない要素が来たら、head を閉じて after head で処理し直す
+                            // ここがないと head が省略されている html document で無限ループが出るらしい
+                            self.pop_until(ElementKind::Head);
+                            self.current_mode = InsertionMode::AfterHead;
+                            continue;
                         },
                         Some(HtmlToken::EndTag { ref tag }) => {
                             if tag == "head" {
@@ -155,9 +181,9 @@ impl HtmlParser {
                                 continue;
                             }
                         },
-                        Some(HtmlToken::StartTag { ref tag, self_closing: _, ref attributes }) => {
+                        Some(HtmlToken::StartTag { ref tag, self_closing, ref attributes }) => {
                             if tag == "body" {
-                                self.insert_element(tag, attributes.to_vec());
+                                self.insert_element(tag, attributes.to_vec(), self_closing);
                                 self.current_mode = InsertionMode::InBody;
                                 token = self.tokenizer.next();
                                 continue;
@@ -168,21 +194,44 @@ impl HtmlParser {
                         },
                         _ => {}
                     }
-                    self.insert_element("body", Vec::new());
+                    self.insert_element("body", Vec::new(), false);
                     self.current_mode = InsertionMode::InBody;
                     continue;
                 },
                 InsertionMode::InBody => {
                     match token {
-                        Some(HtmlToken::StartTag { ref tag, self_closing: _, ref attributes }) => {
+                        Some(HtmlToken::StartTag { ref tag, self_closing, ref attributes }) => {
                             match tag.as_str() {
-                                "p" | "a" => {
-                                    self.insert_element(tag, attributes.to_vec());
+                                "html" | "head" | "body" => {
+                                    // 本当は属性をマージしたりするのだが、サボって単に無視する
+                                    token = self.tokenizer.next();
+                                    continue;
+                                }
+                                "style" | "script" | "title" => {
+                                    // body の中に出てきた場合も in head と同じように扱う
+                                    self.insert_element(tag, attributes.to_vec(), self_closing);
+                                    self.original_mode = self.current_mode;
+                                    self.current_mode = InsertionMode::Text;
+                                    token = self.tokenizer.next();
+                                    continue;
+                                }
+                                "textarea" => {
+                                    // textarea の中身は tokenizer が RCDATA として扱うので、Text モードで文字を入れていく
+                                    self.insert_element(tag, attributes.to_vec(), self_closing);
+                                    self.original_mode = self.current_mode;
+                                    self.current_mode = InsertionMode::Text;
                                     token = self.tokenizer.next();
                                     continue;
                                 }
                                 _ => {
+                                    // 知らない要素も ElementKind::Unknown として挿入する
+                                    // 本当は "has a p element in button scope" を見るのだが、スコープの概念は実装していないので stack 全体を見る
+                                    if closes_p_element(tag) && self.contain_in_stack(ElementKind::P) {
+                                        self.pop_until(ElementKind::P);
+                                    }
+                                    self.insert_element(tag, attributes.to_vec(), self_closing);
                                     token = self.tokenizer.next();
+                                    continue;
                                 }
                             }
                         }
@@ -211,14 +260,14 @@ impl HtmlParser {
                                     }
                                     continue;
                                 }
-                                "p" | "a" => {
-                                    let element_kind = ElementKind::from_str(tag).expect("ha?");
-                                    token = self.tokenizer.next();
-                                    self.pop_until(element_kind);
-                                    continue;
-                                }
                                 _ => {
+                                    // 開いていない要素の終了タグは parse error なので無視する
+                                    let element_kind = ElementKind::from(tag.as_str());
+                                    if self.contain_in_stack(element_kind.clone()) {
+                                        self.pop_until(element_kind);
+                                    }
                                     token = self.tokenizer.next();
+                                    continue;
                                 }
                             }
                         }
@@ -250,6 +299,18 @@ impl HtmlParser {
                                 token = self.tokenizer.next();
                                 continue;
                             }
+                            if tag == "title" {
+                                self.pop_until(ElementKind::Title);
+                                self.current_mode = self.original_mode;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
+                            if tag == "textarea" {
+                                self.pop_until(ElementKind::Textarea);
+                                self.current_mode = self.original_mode;
+                                token = self.tokenizer.next();
+                                continue;
+                            }
                         }
                         Some(HtmlToken::Char(c)) => {
                             self.insert_char(c);
@@ -305,44 +366,34 @@ impl HtmlParser {
         Node::new(NodeKind::Element(Element::new(tag, attributes)))
     }
 
-    fn insert_element(&mut self, tag: &str, attributes: Vec<HtmlTagAttribute>) {
+    fn insert_element(&mut self, tag: &str, attributes: Vec<HtmlTagAttribute>, self_closing: bool) {
         let window = &self.window;
-        let mut current = match self.stack_of_open_elements.last() {
+        let current = match self.stack_of_open_elements.last() {
             Some(n) => n.clone(),
             None => window.borrow().document(),
         };
 
         let node = Rc::new(RefCell::new(self.create_element(tag, attributes)));
 
-        if current.borrow().first_child().is_some() {
-            // なんかもうちょいどうにかならんかな。last_sibling が some であることはこのブロックにおける不変条件なので、それが明確になるようにしたい
-            let mut last_sibling = current.borrow().first_child();
-            loop {
-                last_sibling = match last_sibling {
-                    Some(ref node) => {
-                        if node.borrow().next_sibling().is_some() {
-                            node.borrow().next_sibling()
-                        } else {
-                            break;
-                        }
-                    }
-                    None => unimplemented!("ha?")
-                }
-            }
-
-            // ここで mutate したいので Node の Fields は RefCell で包まないといけない。なるほど～
-            // Rc::get_mut するのは、一般には Rc での参照が1つとは限らないので上手くいかない。
-            // let a = Rc::get_mut(&mut last_sibling.unwrap()).unwrap().set_next_sibling(Some(Rc::clone(&node)));
-            last_sibling.as_ref().unwrap().borrow_mut().set_next_sibling(Some(Rc::clone(&node)));
+        append_child(&current, &node);
 
-            node.borrow_mut().set_previous_sibling(Rc::downgrade(&last_sibling.unwrap()));
-        } else {
-            current.borrow_mut().set_first_child(Some(Rc::clone(&node)));
+        // void 要素には終了タグがないので、stack に積むと後ろの要素を子として取り込んでしまう
+        // [] 13.1.2.1 Start tags | HTML Standard
+        // https://html.spec.whatwg.org/multipage/syntax.html#start-tags
+        // ----- Cited From Reference -----
+        // On void elements, it does not mark the start tag as self-closing but instead is unnecessary and has no effect of any kind.
+        // On foreign elements, it marks the start tag as self-closing.
+        // On normal elements, the existence of the slash is a parse error.
+        // --------------------------------
+        // <div/> のような知っている要素の / は無視するが、知らない要素は svg の中身のような foreign element かもしれないので / があれば閉じておく
+        let closes_itself = match node.borrow().get_element_kind() {
+            Some(k) => k.is_void() || (self_closing && matches!(k, ElementKind::Unknown(_))),
+            None => false,
+        };
+        if closes_itself {
+            return;
         }
 
-        current.borrow_mut().set_last_child(Rc::downgrade(&node));
-        node.borrow_mut().set_parent(Rc::downgrade(&current));
-
         self.stack_of_open_elements.push(node);
     }
 
@@ -353,7 +404,7 @@ impl HtmlParser {
                 None => return
             };
 
-            if current.borrow().get_element_kind() == Some(kind) {
+            if current.borrow().get_element_kind().as_ref() == Some(&kind) {
                 return;
             }
         }
@@ -361,7 +412,7 @@ impl HtmlParser {
 
     fn contain_in_stack(&self, kind: ElementKind) -> bool {
         // find で書けるから書いたけど別にわかりやすくなった気はしないな
-        if let Some(_) = self.stack_of_open_elements.iter().find(|x| x.borrow().get_element_kind() == Some(kind)) {
+        if let Some(_) = self.stack_of_open_elements.iter().find(|x| x.borrow().get_element_kind().as_ref() == Some(&kind)) {
             true
         } else {
             false
@@ -392,11 +443,19 @@ impl HtmlParser {
             None => return, // 本当はこの枝に入る時点で何かがおかしいのでいい感じに弾きたいんだよな。しかしサボってエラーを握りつぶすことにする
         };
 
-        // 現在参照しているノードが Text ならそいつに push すればいいのでそうする
-        if let NodeKind::Text(mut s) = current.borrow_mut().node_kind() {
-            s.push(c);
-            return;
-        };
+        // [] 13.2.6.1 Creating and inserting nodes | HTML Standard
+        // https://html.spec.whatwg.org/multipage/parsing.html#insert-a-character
+        // ----- Cited From Reference -----
+        // If there is a Text node immediately before the adjusted insertion location, then append data to that Text node's data.
+        // Otherwise, create a new Text node whose data is data and whose node document is the same as that of the element in which the adjusted insertion location finds itself, and insert the newly created node at the adjusted insertion location.
+        // --------------------------------
+        // Text は stack of open elements には積まないので、current node の最後の子を見て直前の Text を探す
+        let last_child = current.borrow().last_child().upgrade();
+        if let Some(last) = last_child {
+            if last.borrow_mut().push_char_to_text(c) {
+                return;
+            }
+        }
 
         if c == '\n' || c == ' ' {
             return;
@@ -404,44 +463,14 @@ impl HtmlParser {
 
         let node = Rc::new(RefCell::new(self.create_char(c)));
 
-        if current.borrow().first_child().is_some() {
-            // 本だとこのパートだけ last_sibling のサーチをサボってるんだけど、やったほうがいいのでは？？？？
-            // なんかもうちょいどうにかならんかな（2）。last_sibling が some であることはこのブロックにおける不変条件なので、それが明確になるようにしたい
-            let mut last_sibling = current.borrow().first_child();
-            loop {
-                last_sibling = match last_sibling {
-                    Some(ref node) => {
-                        if node.borrow().next_sibling().is_some() {
-                            node.borrow().next_sibling()
-                        } else {
-                            break;
-                        }
-                    }
-                    None => unimplemented!("ha?")
-                }
-            }
-
-            // ここで mutate したいので Node の Fields は RefCell で包まないといけない。なるほど～
-            // Rc::get_mut するのは、一般には Rc での参照が1つとは限らないので上手くいかない。
-            // let a = Rc::get_mut(&mut last_sibling.unwrap()).unwrap().set_next_sibling(Some(Rc::clone(&node)));
-            last_sibling.as_ref().unwrap().borrow_mut().set_next_sibling(Some(Rc::clone(&node)));
-
-            node.borrow_mut().set_previous_sibling(Rc::downgrade(&last_sibling.unwrap()));
-        } else {
-            current.borrow_mut().set_first_child(Some(Rc::clone(&node)));
-        }
-
-        current.borrow_mut().set_last_child(Rc::downgrade(&node));
-        node.borrow_mut().set_parent(Rc::downgrade(&current));
-
-        self.stack_of_open_elements.push(node);
+        append_child(&current, &node);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{alloc::string::ToString, renderer::html::html_tag_attribute::AttributeField};
+    use crate::{alloc::string::ToString, renderer::{dom::api::get_elements_by_tag_name, html::html_tag_attribute::AttributeField}};
     use alloc::vec;
 
     #[test]
@@ -615,4 +644,467 @@ mod tests {
             text
         );
     }
+
+    #[test]
+    fn test_title() {
+        let html = "<html><head><title>a<b></title></head><body></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let head = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html");
+
+        let title = head
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of head");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "title",
+                Vec::new()
+            ))))),
+            title
+        );
+
+        // <b> は要素ではなく文字として扱われる
+        let text = title
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of title");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("a<b>".to_string())))),
+            text
+        );
+        assert!(title.borrow().next_sibling().is_none());
+
+        let body = head
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "body",
+                Vec::new()
+            ))))),
+            body
+        );
+    }
+
+    #[test]
+    fn test_after_head_moves_to_in_body() {
+        let html = "<html><head></head><body><p>x</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "body",
+                Vec::new()
+            ))))),
+            body
+        );
+
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p
+        );
+
+        let text = p
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of p");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("x".to_string())))),
+            text
+        );
+    }
+
+    #[test]
+    fn test_implicit_body_after_head() {
+        let html = "<html><head></head><p>x</p></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "body",
+                Vec::new()
+            ))))),
+            body
+        );
+
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p
+        );
+    }
+
+    #[test]
+    fn test_start_tags_in_body() {
+        let html = "<body><p>hi</p><a>x</a></body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p
+        );
+
+        let a = p
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of p");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "a",
+                Vec::new()
+            ))))),
+            a
+        );
+        assert!(a.borrow().next_sibling().is_none());
+    }
+
+    #[test]
+    fn test_unknown_start_tag_in_body() {
+        let html = "<body><foo></foo><p>y</p></body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+
+        // foo は Unknown な要素として挿入される
+        let foo = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(
+            Some(ElementKind::Unknown("foo".to_string())),
+            foo.borrow().get_element_kind()
+        );
+
+        let p = foo
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of foo");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p
+        );
+        assert!(p.borrow().next_sibling().is_none());
+    }
+
+    #[test]
+    fn test_implicitly_close_p() {
+        let html = "<body><p>one<p>two</body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+
+        let p1 = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p1
+        );
+
+        // 2つ目の p は1つ目の p の子ではなく兄弟になる
+        let p2 = p1
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of p");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
+                "p",
+                Vec::new()
+            ))))),
+            p2
+        );
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("one".to_string())))),
+            p1.borrow().first_child().expect("failed to get a first child of p")
+        );
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("two".to_string())))),
+            p2.borrow().first_child().expect("failed to get a first child of p")
+        );
+    }
+
+    #[test]
+    fn test_meta_in_head() {
+        let html = "<html><head><meta charset=utf-8><title>x</title></head><body></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let head = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html");
+
+        let meta = head
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of head");
+        assert_eq!(
+            Some(ElementKind::Unknown("meta".to_string())),
+            meta.borrow().get_element_kind()
+        );
+
+        // meta は閉じられるので、title は meta の子ではなく兄弟になる
+        let title = meta
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of meta");
+        assert_eq!(Some(ElementKind::Title), title.borrow().get_element_kind());
+    }
+
+    #[test]
+    fn test_div_and_span() {
+        let html = "<body><div><span>x</span></div></body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let div = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(Some(ElementKind::Div), div.borrow().get_element_kind());
+
+        let span = div
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of div");
+        assert_eq!(Some(ElementKind::Span), span.borrow().get_element_kind());
+    }
+
+    #[test]
+    fn test_script_in_head() {
+        let html = "<html><head><script>x</script></head><body><p>y</p></body></html>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let head = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html");
+
+        let script = head
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of head");
+        assert_eq!(Some(ElementKind::Script), script.borrow().get_element_kind());
+
+        // script がきちんと閉じられるので、p は body の下に入る
+        let body = head
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+        let p = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(Some(ElementKind::P), p.borrow().get_element_kind());
+    }
+
+    #[test]
+    fn test_void_element() {
+        let html = "<body><img><p>x</p><br>y</body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = document
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of document")
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of html")
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of head");
+
+        let img = body
+            .borrow()
+            .first_child()
+            .expect("failed to get a first child of body");
+        assert_eq!(Some(ElementKind::Img), img.borrow().get_element_kind());
+        assert!(img.borrow().first_child().is_none());
+
+        // p は img の子ではなく兄弟になる
+        let p = img
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of img");
+        assert_eq!(Some(ElementKind::P), p.borrow().get_element_kind());
+
+        let br = p
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of p");
+        assert_eq!(Some(ElementKind::Br), br.borrow().get_element_kind());
+        assert!(br.borrow().first_child().is_none());
+
+        let text = br
+            .borrow()
+            .next_sibling()
+            .expect("failed to get a next sibling of br");
+        assert_eq!(
+            Rc::new(RefCell::new(Node::new(NodeKind::Text("y".to_string())))),
+            text
+        );
+    }
+
+    #[test]
+    fn test_consecutive_chars_in_one_text_node() {
+        let html = "<body>abc<p>d</p>e</body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = &get_elements_by_tag_name(&document, "body")[0];
+        let text = body.borrow().first_child().expect("failed to get a first child of body");
+        assert_eq!(NodeKind::Text("abc".to_string()), text.borrow().kind);
+
+        // 文字の後ろの要素は Text の子ではなく兄弟になる
+        let p = text.borrow().next_sibling().expect("failed to get a next sibling of text");
+        assert_eq!(Some(ElementKind::P), p.borrow().get_element_kind());
+        assert!(text.borrow().first_child().is_none());
+
+        let text = p.borrow().next_sibling().expect("failed to get a next sibling of p");
+        assert_eq!(NodeKind::Text("e".to_string()), text.borrow().kind);
+        assert!(text.borrow().next_sibling().is_none());
+    }
+
+    #[test]
+    fn test_self_closing_tag() {
+        let html = "<body><br/><p>x</p><foo/><img/><div/><span>y</span></body>".to_string();
+        let t = HtmlTokenizer::new(html);
+        let window = HtmlParser::new(t).construct_tree();
+        let document = window.borrow().document();
+
+        let body = &get_elements_by_tag_name(&document, "body")[0];
+        let br = body.borrow().first_child().expect("failed to get a first child of body");
+        assert_eq!(Some(ElementKind::Br), br.borrow().get_element_kind());
+        let p = br.borrow().next_sibling().expect("failed to get a next sibling of br");
+        assert_eq!(Some(ElementKind::P), p.borrow().get_element_kind());
+        let foo = p.borrow().next_sibling().expect("failed to get a next sibling of p");
+        assert_eq!(Some(ElementKind::Unknown("foo".to_string())), foo.borrow().get_element_kind());
+        let img = foo.borrow().next_sibling().expect("failed to get a next sibling of foo");
+        assert_eq!(Some(ElementKind::Img), img.borrow().get_element_kind());
+        let div = img.borrow().next_sibling().expect("failed to get a next sibling of img");
+        assert_eq!(Some(ElementKind::Div), div.borrow().get_element_kind());
+        assert!(div.borrow().next_sibling().is_none());
+
+        // <div/> の / は無視されるので、後ろの span は div の子になる
+        let span = div.borrow().first_child().expect("failed to get a first child of div");
+        assert_eq!(Some(ElementKind::Span), span.borrow().get_element_kind());
+    }
 }
@@ -1,3 +1,5 @@
+use core::fmt;
+
 use alloc::{string::String, vec::Vec};
 use crate::renderer::html::html_tag_attribute::{AttributeField, HtmlTagAttribute};
 
@@ -27,7 +29,7 @@ pub enum HtmlToken {
 // [] 13.2.5 Tokenization | HTML Standard
 // https://html.spec.whatwg.org/multipage/parsing.html#tokenization
 // ↑ で規定のある State の一部を実装する。本当は80種類あるのだが、全部実装すると日が暮れる……
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenizerState {
     Data, // https://html.spec.whatwg.org/multipage/parsing.html#data-state
     TagOpen, // https://html.spec.whatwg.org/multipage/parsing.html#tag-open-state
@@ -47,15 +49,99 @@ pub enum TokenizerState {
     ScriptDataEndTagOpen, // https://html.spec.whatwg.org/multipage/parsing.html#script-data-end-tag-open-state
     ScriptDataEndTagName, // https://html.spec.whatwg.org/multipage/parsing.html#script-data-end-tag-name-state
     TemporaryBuffer, // whatwg 上で規定はないが、実装を簡単にするために実装する
+    CharacterReference, // https://html.spec.whatwg.org/multipage/parsing.html#character-reference-state
+    NamedCharacterReference, // https://html.spec.whatwg.org/multipage/parsing.html#named-character-reference-state
+    NumericCharacterReference, // https://html.spec.whatwg.org/multipage/parsing.html#numeric-character-reference-state
+    HexadecimalCharacterReferenceStart, // https://html.spec.whatwg.org/multipage/parsing.html#hexadecimal-character-reference-start-state
+    DecimalCharacterReferenceStart, // https://html.spec.whatwg.org/multipage/parsing.html#decimal-character-reference-start-state
+    HexadecimalCharacterReference, // https://html.spec.whatwg.org/multipage/parsing.html#hexadecimal-character-reference-state
+    DecimalCharacterReference, // https://html.spec.whatwg.org/multipage/parsing.html#decimal-character-reference-state
+    Rcdata, // https://html.spec.whatwg.org/multipage/parsing.html#rcdata-state
+    RcdataLessThanSign, // https://html.spec.whatwg.org/multipage/parsing.html#rcdata-less-than-sign-state
+    RcdataEndTagOpen, // https://html.spec.whatwg.org/multipage/parsing.html#rcdata-end-tag-open-state
+    RcdataEndTagName, // https://html.spec.whatwg.org/multipage/parsing.html#rcdata-end-tag-name-state
+}
+
+// [] 13.2.5.32 Before attribute name state | HTML Standard
+// https://html.spec.whatwg.org/multipage/parsing.html#before-attribute-name-state
+// ----- Cited From Reference -----
+// U+0009 CHARACTER TABULATION (tab)
+// U+000A LINE FEED (LF)
+// U+000C FORM FEED (FF)
+// U+0020 SPACE
+// --------------------------------
+// タグの中の状態ではこの4文字を空白として扱う
+fn is_html_whitespace(c: char) -> bool {
+    c == '\t' || c == '\n' || c == '\u{000C}' || c == ' '
+}
+
+// [] 13.5 Named character references | HTML Standard
+// https://html.spec.whatwg.org/multipage/named-characters.html#named-character-references
+// 本当は2000種類以上あるのだが、よく使うものだけを実装する
+const NAMED_CHARACTER_REFERENCES: [(&str, char); 6] = [
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{00A0}'),
+];
+
+fn lookup_named_character_reference(name: &str) -> Option<char> {
+    NAMED_CHARACTER_REFERENCES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, c)| *c)
+}
+
+// [] 13.2.5.80 Numeric character reference end state | HTML Standard
+// https://html.spec.whatwg.org/multipage/parsing.html#numeric-character-reference-end-state
+// ----- Cited From Reference -----
+// If the number is 0x00, then this is a null-character-reference parse error. Set the character reference code to 0xFFFD.
+// If the number is greater than 0x10FFFF, then this is a character-reference-outside-unicode-range parse error. Set the character reference code to 0xFFFD.
+// If the number is a surrogate, then this is a surrogate-character-reference parse error. Set the character reference code to 0xFFFD.
+// --------------------------------
+// 本当は 0x80..=0x9F を Windows-1252 の文字に読み替える表もあるのだが、サボる
+fn numeric_character_reference_to_char(code: u32) -> char {
+    if code == 0 {
+        return '\u{FFFD}';
+    }
+
+    // 範囲外やサロゲートは char::from_u32 が None を返してくれる
+    char::from_u32(code).unwrap_or('\u{FFFD}')
+}
+
+// tokenizer が見つけた parse error。message は仕様で決められた名前 (eof-in-tag など)
+// line と column はエラーが起きたときの current input character の位置で、HtmlTokenizer の line / column と同じ数え方
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct HtmlTokenizer {
     state: TokenizerState,
-    pos: usize,
+    return_state: TokenizerState, // https://html.spec.whatwg.org/multipage/parsing.html#return-state
+    character_reference_code: u32, // https://html.spec.whatwg.org/multipage/parsing.html#character-reference-code
+    pos: usize, // 次に消費する文字の input 中のバイト位置
+    line: usize, // current input character の行。1 始まり
+    column: usize, // current input character の列。1 始まりで、行頭の文字を消費する前は 0
+    errors: Vec<ParseError>,
+    current_char: char, // https://html.spec.whatwg.org/multipage/parsing.html#current-input-character
+    eof: bool,
     reconsume: bool,
     latest_token: Option<HtmlToken>,
-    input: Vec<char>,
+    last_start_tag: String, // appropriate end tag の判定に使う
+    // Vec<char> に展開するとメモリを 4 倍近く食うので、受け取った String のまま持ってバイト位置で読み進める
+    input: String,
     buf: String,
 }
 
@@ -63,16 +149,26 @@ impl HtmlTokenizer {
     pub fn new(html: String) -> Self {
         Self {
             state: TokenizerState::Data,
+            return_state: TokenizerState::Data,
+            character_reference_code: 0,
             pos: 0,
+            line: 1,
+            column: 0,
+            errors: Vec::new(),
+            current_char: '\0',
+            eof: false,
             reconsume: false,
             latest_token: None,
-            input: html.chars().collect(),
+            last_start_tag: String::new(),
+            input: html,
             buf: String::new(),
         }
     }
 
+    // 最後の文字を消費したあと、さらにもう1文字消費しようとしたら EOF とみなす
+    // つまり EOF は input の末尾の「次の文字」として扱う
     fn is_eof(&self) -> bool {
-        self.pos > self.input.len()
+        self.eof
     }
 
     fn consume_next_character(&mut self) -> char {
@@ -88,14 +184,56 @@ impl HtmlTokenizer {
             //  The current input character is the last character to have been consumed.
             // --------------------------------
             self.reconsume = false;
-            self.input[self.pos - 1]
+            self.current_char
         } else {
-            self.pos += 1;
-            self.input[self.pos - 1]
+            match self.input[self.pos..].chars().next() {
+                Some(c) => {
+                    self.pos += c.len_utf8();
+                    // 直前の文字が改行なら、この文字から次の行になる
+                    if self.current_char == '\n' {
+                        self.line += 1;
+                        self.column = 0;
+                    }
+                    self.column += 1;
+                    c
+                }
+                None => {
+                    self.eof = true;
+                    // EOF のときに返す文字には意味がないので、各 state では必ず is_eof() で判定すること
+                    '\0'
+                }
+            }
         };
+
+        self.current_char = c;
         c
     }
 
+    // [] 13.2.2 Parse errors | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#parse-errors
+    // ----- Cited From Reference -----
+    // This specification defines the parsing rules for HTML documents, whether they are syntactically correct or not. Certain points in the parsing algorithm are said to be parse errors. The error handling for parse errors is well-defined (that's the processing rules described throughout this specification), but user agents, while parsing an HTML document, may abort the parser at the first parse error that they encounter for which they do not wish to apply the rules described in this specification.
+    // --------------------------------
+    // パースは止めずに、どこで何が起きたかだけを記録しておく
+    fn parse_error(&mut self, name: &str) {
+        self.errors.push(ParseError { message: String::from(name), line: self.line, column: self.column });
+    }
+
+    // これまでに見つかった parse error。トークンの出力には影響しない
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    // テストやデバッグ用に、現在の state を覗けるようにしておく
+    pub fn state(&self) -> &TokenizerState {
+        &self.state
+    }
+
+    // 最後までトークナイズして、Eof を除いたトークンをまとめて返す
+    pub fn tokenize(self) -> Vec<HtmlToken> {
+        self.take_while(|t| *t != HtmlToken::Eof).collect()
+    }
+
     fn create_start_tag(&mut self) {
         self.latest_token = Some(
             HtmlToken::StartTag { tag: String::new(), self_closing: false, attributes: Vec::new() }
@@ -126,16 +264,52 @@ impl HtmlTokenizer {
         self.latest_token = None;
         assert!(self.latest_token.is_none());
 
+        if let Some(HtmlToken::StartTag { ref tag, self_closing: _, attributes: _ }) = t {
+            self.last_start_tag = tag.clone();
+
+            // 本当は tree construction 側が tokenizer の state を切り替えるのだが、
+            // parser から tokenizer の state をいじる口がないので tokenizer 側で判断してしまう
+            // https://html.spec.whatwg.org/multipage/parsing.html#generic-rcdata-element-parsing-algorithm
+            if tag == "title" || tag == "textarea" {
+                self.state = TokenizerState::Rcdata;
+            }
+
+            // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inhead (A start tag whose tag name is "script")
+            if tag == "script" {
+                self.state = TokenizerState::ScriptData;
+            }
+        }
+
         t
     }
 
+    // [] appropriate end tag token | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#appropriate-end-tag-token
+    // ----- Cited From Reference -----
+    // An appropriate end tag token is an end tag token whose tag name matches the tag name of the last start tag to have been emitted from this tokenizer, if any. If no start tag has been emitted from this tokenizer, then no end tag token is appropriate.
+    // --------------------------------
+    fn is_appropriate_end_tag(&self) -> bool {
+        match self.latest_token {
+            Some(HtmlToken::EndTag { ref tag }) => !self.last_start_tag.is_empty() && *tag == self.last_start_tag,
+            _ => false,
+        }
+    }
+
+    // [] 13.2.5 Tokenization | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#tokenization
+    // ----- Cited From Reference -----
+    // When an end tag token is emitted with attributes, that is an end-tag-with-attributes parse error.
+    // When an end tag token is emitted with its self-closing flag set, that is an end-tag-with-trailing-solidus parse error.
+    // --------------------------------
+    // </p class=a> や </p/> のような終了タグも属性や / を読み進めるが、EndTag は属性を持たないので読んだものは捨てる
     fn start_new_attribute(&mut self) {
         assert!(self.latest_token.is_some());
 
         if let Some(t) = self.latest_token.as_mut() {
             match t {
                 HtmlToken::StartTag { tag: _, self_closing: _, attributes } => attributes.push(HtmlTagAttribute::new()),
-                _ => panic!("latest_token must be StartTag"),
+                HtmlToken::EndTag { tag: _ } => {}
+                _ => panic!("latest_token must be either StartTag or EndTag"),
             }
         }
     }
@@ -151,18 +325,64 @@ impl HtmlTokenizer {
 
                     attributes[len - 1].add_char(c, field)
                 },
-                _ => panic!("latest_token should be StartTag"),
+                HtmlToken::EndTag { tag: _ } => {}
+                _ => panic!("latest_token must be either StartTag or EndTag"),
             }
         }
     }
 
+    // [] 13.2.5.36 Attribute value (double-quoted) state | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(double-quoted)-state
+    // ----- Cited From Reference -----
+    // EOF
+    // This is an eof-in-tag parse error. Emit an end-of-file token.
+    // --------------------------------
+    // 仕様どおりだと作りかけのタグは捨てられるが、<a href="/x のように途中で切れた入力でも属性を失わないように、
+    // 作りかけのタグを先に出力して、次の呼び出しで EOF を reconsume して Eof を返す
+    fn emit_latest_token_at_eof(&mut self) -> Option<HtmlToken> {
+        self.parse_error("eof-in-tag");
+        self.reconsume = true;
+        self.state = TokenizerState::Data;
+        self.emit_latest_token()
+    }
+
+    // [] 13.2.5 Tokenization | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#charref-in-attribute
+    // ----- Cited From Reference -----
+    // A character reference is said to be consumed as part of an attribute if the return state is either attribute value (double-quoted) state, attribute value (single-quoted) state, or attribute value (unquoted) state.
+    // --------------------------------
+    fn is_consumed_as_part_of_an_attribute(&self) -> bool {
+        matches!(
+            self.return_state,
+            TokenizerState::AttributeValueDoubleQuoted
+                | TokenizerState::AttributeValueSingleQuoted
+                | TokenizerState::AttributeValueUnQuoted
+        )
+    }
+
+    // [] 13.2.5 Tokenization | HTML Standard
+    // https://html.spec.whatwg.org/multipage/parsing.html#flush-code-points-consumed-as-a-character-reference
+    // ----- Cited From Reference -----
+    // When a state says to flush code points consumed as a character reference, it means that for each code point in the temporary buffer (in the order they were added to the buffer) user agent must append the code point from the buffer to the current attribute's value if the character reference was consumed as part of an attribute, or emit the code point as a character token otherwise.
+    // --------------------------------
+    // 属性値の中なら属性に追加して None を、そうでなければ文字トークンを返す
+    fn flush_code_point(&mut self, c: char) -> Option<HtmlToken> {
+        if self.is_consumed_as_part_of_an_attribute() {
+            self.append_character_to_attribute(c, AttributeField::Value);
+            return None;
+        }
+
+        Some(HtmlToken::Char(c))
+    }
+
     fn set_self_closing_flag(&mut self) {
         assert!(self.latest_token.is_some());
 
         if let Some(t) = self.latest_token.as_mut() {
             match t {
                 HtmlToken::StartTag { tag: _, self_closing, attributes: _ } => *self_closing = true,
-                _ => panic!("latest_token must be StartTag")
+                HtmlToken::EndTag { tag: _ } => {}
+                _ => panic!("latest_token must be either StartTag or EndTag")
             }
         }
     }
@@ -172,7 +392,9 @@ impl Iterator for HtmlTokenizer {
     type Item = HtmlToken;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos >= self.input.len() { // ここは is_eof ではダメ？
+        // Eof token を返したあとは None を返して iteration を終える
+        // ただし reconsume が残っている場合は、EOF をもう一度処理する必要があるので抜けない
+        if self.is_eof() && !self.reconsume {
             return None
         }
 
@@ -180,6 +402,12 @@ impl Iterator for HtmlTokenizer {
             let c = self.consume_next_character();
             match self.state {
                 TokenizerState::Data => {
+                    if c == '&' {
+                        self.return_state = TokenizerState::Data;
+                        self.state = TokenizerState::CharacterReference;
+                        continue;
+                    }
+
                     if c == '<' {
                         self.state = TokenizerState::TagOpen;
                         continue
@@ -213,7 +441,7 @@ impl Iterator for HtmlTokenizer {
                 },
                 TokenizerState::EndTagOpen => {
                     if self.is_eof() {
-                        // 本当はパースエラーにする必要がある
+                        self.parse_error("eof-before-tag-name");
                         return Some(HtmlToken::Eof);
                     }
 
@@ -226,7 +454,7 @@ impl Iterator for HtmlTokenizer {
                     // 本当は > とかが来たらパースエラーにする必要があるのだが、本に沿っていったんこのままにする
                 },
                 TokenizerState::TagName => {
-                    if c == ' ' { // 本当は tab, LF, FF もこの枝
+                    if is_html_whitespace(c) {
                         self.state = TokenizerState::BeforeAttributeName;
                         continue;
                     }
@@ -247,13 +475,23 @@ impl Iterator for HtmlTokenizer {
                     }
 
                     if self.is_eof() {
+                        self.parse_error("eof-in-tag");
                         return Some(HtmlToken::Eof);
                     }
 
-                    // 本当は NULL 文字は U+FFFD に変換するがめんどいのでそのまま
+                    // NULL 文字は unexpected-null-character parse error で、U+FFFD に置き換える
+                    if c == '\0' {
+                        self.append_tag_name('\u{FFFD}');
+                        continue;
+                    }
+
                     self.append_tag_name(c);
                 },
                 TokenizerState::BeforeAttributeName => {
+                    if is_html_whitespace(c) {
+                        continue;
+                    }
+
                     if c == '/' || c == '>' || self.is_eof() {
                         self.reconsume = true;
                         self.state = TokenizerState::AfterAttributeName;
@@ -267,7 +505,7 @@ impl Iterator for HtmlTokenizer {
                     // 本当は = の場合は別の処理がある  とか space を無視するとか色々ある
                 },
                 TokenizerState::AttributeName => {
-                    if c == ' ' || c == '/' || c == '>' || self.is_eof() {
+                    if is_html_whitespace(c) || c == '/' || c == '>' || self.is_eof() {
                         self.reconsume = true;
                         self.state = TokenizerState::AfterAttributeName;
                         continue;
@@ -283,10 +521,15 @@ impl Iterator for HtmlTokenizer {
                         continue;
                     }
 
+                    if c == '\0' {
+                        self.append_character_to_attribute('\u{FFFD}', AttributeField::Name);
+                        continue;
+                    }
+
                     self.append_character_to_attribute(c, AttributeField::Name);
                 },
                 TokenizerState::AfterAttributeName => {
-                    if c == ' ' {
+                    if is_html_whitespace(c) {
                         continue;
                     }
 
@@ -306,6 +549,7 @@ impl Iterator for HtmlTokenizer {
                     }
 
                     if self.is_eof() {
+                        self.parse_error("eof-in-tag");
                         return Some(HtmlToken::Eof);
                     }
 
@@ -314,7 +558,7 @@ impl Iterator for HtmlTokenizer {
                     self.start_new_attribute();
                 },
                 TokenizerState::BeforeAttributeValue => {
-                    if c == ' ' {
+                    if is_html_whitespace(c) {
                         continue;
                     }
 
@@ -339,8 +583,14 @@ impl Iterator for HtmlTokenizer {
                         continue;
                     }
 
+                    if c == '&' {
+                        self.return_state = TokenizerState::AttributeValueDoubleQuoted;
+                        self.state = TokenizerState::CharacterReference;
+                        continue;
+                    }
+
                     if self.is_eof() {
-                        return Some(HtmlToken::Eof);
+                        return self.emit_latest_token_at_eof();
                     }
 
                     self.append_character_to_attribute(c, AttributeField::Value);
@@ -351,31 +601,43 @@ impl Iterator for HtmlTokenizer {
                         continue;
                     }
 
+                    if c == '&' {
+                        self.return_state = TokenizerState::AttributeValueSingleQuoted;
+                        self.state = TokenizerState::CharacterReference;
+                        continue;
+                    }
+
                     if self.is_eof() {
-                        return Some(HtmlToken::Eof);
+                        return self.emit_latest_token_at_eof();
                     }
 
                     self.append_character_to_attribute(c, AttributeField::Value);
                 },
                 TokenizerState::AttributeValueUnQuoted => {
-                    if c == ' ' {
+                    if is_html_whitespace(c) {
                         self.state = TokenizerState::BeforeAttributeName;
                         continue;
                     }
 
+                    if c == '&' {
+                        self.return_state = TokenizerState::AttributeValueUnQuoted;
+                        self.state = TokenizerState::CharacterReference;
+                        continue;
+                    }
+
                     if c == '>' {
                         self.state = TokenizerState::Data;
                         return self.emit_latest_token();
                     }
 
                     if self.is_eof() {
-                        return Some(HtmlToken::Eof);
+                        return self.emit_latest_token_at_eof();
                     }
 
                     self.append_character_to_attribute(c, AttributeField::Value);
                 },
                 TokenizerState::AfterAttributeValueQuoted => {
-                    if c == ' ' {
+                    if is_html_whitespace(c) {
                         self.state = TokenizerState::BeforeAttributeName;
                         continue;
                     }
@@ -391,6 +653,7 @@ impl Iterator for HtmlTokenizer {
                     }
 
                     if self.is_eof() {
+                        self.parse_error("eof-in-tag");
                         return Some(HtmlToken::Eof);
                     }
 
@@ -406,6 +669,7 @@ impl Iterator for HtmlTokenizer {
                     }
 
                     if self.is_eof() {
+                        self.parse_error("eof-in-tag");
                         return Some(HtmlToken::Eof);
                     }
                 },
@@ -419,6 +683,10 @@ impl Iterator for HtmlTokenizer {
                         return Some(HtmlToken::Eof);
                     }
 
+                    if c == '\0' {
+                        return Some(HtmlToken::Char('\u{FFFD}'));
+                    }
+
                     return Some(HtmlToken::Char(c));
                 },
                 TokenizerState::ScriptDataLessThanSign => {
@@ -428,6 +696,7 @@ impl Iterator for HtmlTokenizer {
                         continue;
                     }
 
+                    // 本当は <! から始まる script data escaped state もあるのだが、サボる
                     self.reconsume = true;
                     self.state = TokenizerState::ScriptData;
                     return Some(HtmlToken::Char('<'));
@@ -436,16 +705,32 @@ impl Iterator for HtmlTokenizer {
                     if c.is_ascii_alphabetic() {
                         self.reconsume = true;
                         self.state = TokenizerState::ScriptDataEndTagName;
+                        self.create_end_tag();
+                        continue;
                     }
 
+                    // 終了タグではなかったので </ を文字として出力してから ScriptData で reconsume する
+                    self.buf = String::from("</");
                     self.reconsume = true;
-                    self.state = TokenizerState::ScriptData;
-                    return Some(HtmlToken::Char('<')); // 本来は </ を返さないといけない
+                    self.return_state = TokenizerState::ScriptData;
+                    self.state = TokenizerState::TemporaryBuffer;
                 },
                 TokenizerState::ScriptDataEndTagName => {
-                    if c == '>' {
-                        self.state = TokenizerState::Data;
-                        return self.emit_latest_token();
+                    if self.is_appropriate_end_tag() {
+                        if is_html_whitespace(c) {
+                            self.state = TokenizerState::BeforeAttributeName;
+                            continue;
+                        }
+
+                        if c == '/' {
+                            self.state = TokenizerState::SelfClosingStartTag;
+                            continue;
+                        }
+
+                        if c == '>' {
+                            self.state = TokenizerState::Data;
+                            return self.emit_latest_token();
+                        }
                     }
 
                     if c.is_ascii_alphabetic() {
@@ -454,23 +739,220 @@ impl Iterator for HtmlTokenizer {
                         continue;
                     }
 
-                    self.state = TokenizerState::TemporaryBuffer;
+                    // if (a </b) のように、閉じるべきタグではなかったので </ と読んだ文字をそのまま出力する
+                    self.latest_token = None;
                     self.buf = String::from("</") + &self.buf;
-                    self.buf.push(c);
-                    continue;
+                    self.reconsume = true;
+                    self.return_state = TokenizerState::ScriptData;
+                    self.state = TokenizerState::TemporaryBuffer;
                 },
                 TokenizerState::TemporaryBuffer => {
+                    // buf には入力から読んだ文字 (と、読んだ </ や &) しか入らないので、消費したバイト数を超えることはない
+                    // これが崩れていたら buf の扱いがおかしく、reconsume を繰り返して止まらなくなる恐れがあるので早めに落とす
+                    assert!(
+                        self.buf.len() <= self.pos,
+                        "temporary buffer ({} bytes) must not exceed the consumed input ({} bytes)",
+                        self.buf.len(),
+                        self.pos
+                    );
                     self.reconsume = true;
 
                     if self.buf.chars().count() == 0 {
-                        self.state = TokenizerState::ScriptData;
+                        self.state = self.return_state.clone();
                         continue;
                     }
 
                     let c = self.buf.chars().nth(0).expect("self.buf should have at least 1 char");
                     self.buf.remove(0);
+                    if let Some(t) = self.flush_code_point(c) {
+                        return Some(t);
+                    }
+                },
+                TokenizerState::CharacterReference => {
+                    self.buf = String::from("&");
+
+                    if c.is_ascii_alphanumeric() {
+                        self.reconsume = true;
+                        self.state = TokenizerState::NamedCharacterReference;
+                        continue;
+                    }
+
+                    if c == '#' {
+                        self.buf.push(c);
+                        self.state = TokenizerState::NumericCharacterReference;
+                        continue;
+                    }
+
+                    // 文字参照ではなかったので、& をそのまま出力してから return state で reconsume する
+                    self.reconsume = true;
+                    self.state = TokenizerState::TemporaryBuffer;
+                },
+                TokenizerState::NamedCharacterReference => {
+                    if c.is_ascii_alphanumeric() {
+                        self.buf.push(c);
+                        continue;
+                    }
+
+                    // 本当は &amp のようにセミコロンが省略された歴史的な書き方も受け付けるのだが、サボってセミコロン必須とする
+                    if c == ';' {
+                        if let Some(decoded) = lookup_named_character_reference(&self.buf[1..]) {
+                            self.buf = String::new();
+                            self.state = self.return_state.clone();
+                            if let Some(t) = self.flush_code_point(decoded) {
+                                return Some(t);
+                            }
+                            continue;
+                        }
+                    }
+
+                    // 知らない文字参照なので、ここまで読んだ文字をそのまま出力してから return state で reconsume する
+                    self.reconsume = true;
+                    self.state = TokenizerState::TemporaryBuffer;
+                },
+                TokenizerState::NumericCharacterReference => {
+                    self.character_reference_code = 0;
+
+                    if c == 'x' || c == 'X' {
+                        self.buf.push(c);
+                        self.state = TokenizerState::HexadecimalCharacterReferenceStart;
+                        continue;
+                    }
+
+                    self.reconsume = true;
+                    self.state = TokenizerState::DecimalCharacterReferenceStart;
+                },
+                TokenizerState::HexadecimalCharacterReferenceStart => {
+                    if c.is_ascii_hexdigit() {
+                        self.reconsume = true;
+                        self.state = TokenizerState::HexadecimalCharacterReference;
+                        continue;
+                    }
+
+                    // 数字が1つもないのは absence-of-digits-in-numeric-character-reference parse error なので、読んだ文字をそのまま出力する
+                    self.reconsume = true;
+                    self.state = TokenizerState::TemporaryBuffer;
+                },
+                TokenizerState::DecimalCharacterReferenceStart => {
+                    if c.is_ascii_digit() {
+                        self.reconsume = true;
+                        self.state = TokenizerState::DecimalCharacterReference;
+                        continue;
+                    }
+
+                    self.reconsume = true;
+                    self.state = TokenizerState::TemporaryBuffer;
+                },
+                TokenizerState::HexadecimalCharacterReference => {
+                    if let Some(digit) = c.to_digit(16) {
+                        // 巨大な数字を書かれてもオーバーフローしないようにしておく。どうせ範囲外として U+FFFD になる
+                        self.character_reference_code = self.character_reference_code.saturating_mul(16).saturating_add(digit);
+                        continue;
+                    }
+
+                    // Numeric character reference end state は文字を消費しないので、ここでまとめて処理する
+                    // セミコロンがない場合は missing-semicolon-after-character-reference parse error だが、文字参照としては解釈する
+                    if c != ';' {
+                        self.reconsume = true;
+                    }
+                    self.buf = String::new();
+                    self.state = self.return_state.clone();
+                    if let Some(t) = self.flush_code_point(numeric_character_reference_to_char(self.character_reference_code)) {
+                        return Some(t);
+                    }
+                },
+                TokenizerState::DecimalCharacterReference => {
+                    if let Some(digit) = c.to_digit(10) {
+                        self.character_reference_code = self.character_reference_code.saturating_mul(10).saturating_add(digit);
+                        continue;
+                    }
+
+                    if c != ';' {
+                        self.reconsume = true;
+                    }
+                    self.buf = String::new();
+                    self.state = self.return_state.clone();
+                    if let Some(t) = self.flush_code_point(numeric_character_reference_to_char(self.character_reference_code)) {
+                        return Some(t);
+                    }
+                },
+                TokenizerState::Rcdata => {
+                    if c == '&' {
+                        self.return_state = TokenizerState::Rcdata;
+                        self.state = TokenizerState::CharacterReference;
+                        continue;
+                    }
+
+                    if c == '<' {
+                        self.state = TokenizerState::RcdataLessThanSign;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        return Some(HtmlToken::Eof);
+                    }
+
+                    if c == '\0' {
+                        return Some(HtmlToken::Char('\u{FFFD}'));
+                    }
+
                     return Some(HtmlToken::Char(c));
                 },
+                TokenizerState::RcdataLessThanSign => {
+                    if c == '/' {
+                        self.buf = String::new();
+                        self.state = TokenizerState::RcdataEndTagOpen;
+                        continue;
+                    }
+
+                    self.reconsume = true;
+                    self.state = TokenizerState::Rcdata;
+                    return Some(HtmlToken::Char('<'));
+                },
+                TokenizerState::RcdataEndTagOpen => {
+                    if c.is_ascii_alphabetic() {
+                        self.reconsume = true;
+                        self.state = TokenizerState::RcdataEndTagName;
+                        self.create_end_tag();
+                        continue;
+                    }
+
+                    // 終了タグではなかったので </ を文字として出力してから Rcdata で reconsume する
+                    self.buf = String::from("</");
+                    self.reconsume = true;
+                    self.return_state = TokenizerState::Rcdata;
+                    self.state = TokenizerState::TemporaryBuffer;
+                },
+                TokenizerState::RcdataEndTagName => {
+                    if self.is_appropriate_end_tag() {
+                        if is_html_whitespace(c) {
+                            self.state = TokenizerState::BeforeAttributeName;
+                            continue;
+                        }
+
+                        if c == '/' {
+                            self.state = TokenizerState::SelfClosingStartTag;
+                            continue;
+                        }
+
+                        if c == '>' {
+                            self.state = TokenizerState::Data;
+                            return self.emit_latest_token();
+                        }
+                    }
+
+                    if c.is_ascii_alphabetic() {
+                        self.buf.push(c);
+                        self.append_tag_name(c.to_ascii_lowercase());
+                        continue;
+                    }
+
+                    // <title> の中の </b> のように、閉じるべきタグではなかったので読んだ文字をそのまま出力する
+                    self.latest_token = None;
+                    self.buf = String::from("</") + &self.buf;
+                    self.reconsume = true;
+                    self.return_state = TokenizerState::Rcdata;
+                    self.state = TokenizerState::TemporaryBuffer;
+                },
             }
         }
     }
@@ -486,6 +968,7 @@ mod tests {
     fn test_empty() {
         let html = "".to_string();
         let mut tokenizer = HtmlTokenizer::new(html);
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
         assert!(tokenizer.next().is_none());
     }
 
@@ -588,4 +1071,563 @@ mod tests {
             assert_eq!(Some(e), tokenizer.next());
         }
     }
+
+    #[test]
+    fn test_named_character_reference_amp() {
+        let html = "a&amp;b".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::Char('a'),
+            HtmlToken::Char('&'),
+            HtmlToken::Char('b'),
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_named_character_reference_lt() {
+        let html = "&lt;p&gt;".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::Char('<'),
+            HtmlToken::Char('p'),
+            HtmlToken::Char('>'),
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_unknown_named_character_reference() {
+        let html = "&foo;".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::Char('&'),
+            HtmlToken::Char('f'),
+            HtmlToken::Char('o'),
+            HtmlToken::Char('o'),
+            HtmlToken::Char(';'),
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_decimal_character_reference() {
+        let html = "&#65;".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        assert_eq!(Some(HtmlToken::Char('A')), tokenizer.next());
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_hexadecimal_character_reference() {
+        let html = "&#x2764;".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        assert_eq!(Some(HtmlToken::Char('❤')), tokenizer.next());
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_invalid_hexadecimal_character_reference() {
+        // 16進数が1つもない場合は文字参照として扱わず、そのまま出力される
+        let html = "&#xZZ;".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::Char('&'),
+            HtmlToken::Char('#'),
+            HtmlToken::Char('x'),
+            HtmlToken::Char('Z'),
+            HtmlToken::Char('Z'),
+            HtmlToken::Char(';'),
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_out_of_range_character_reference() {
+        let html = "&#x110000;&#0;".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        assert_eq!(Some(HtmlToken::Char('\u{FFFD}')), tokenizer.next());
+        assert_eq!(Some(HtmlToken::Char('\u{FFFD}')), tokenizer.next());
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_whitespace_before_attribute() {
+        let html = "<p\nclass=\"x\">".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let mut attr = HtmlTagAttribute::new();
+        attr.add_char('c', AttributeField::Name);
+        attr.add_char('l', AttributeField::Name);
+        attr.add_char('a', AttributeField::Name);
+        attr.add_char('s', AttributeField::Name);
+        attr.add_char('s', AttributeField::Name);
+        attr.add_char('x', AttributeField::Value);
+
+        let expected = [HtmlToken::StartTag {
+            tag: "p".to_string(),
+            self_closing: false,
+            attributes: vec![attr],
+        }];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_tab_and_form_feed_between_attributes() {
+        let html = "<p\ta=b\u{000C}\tc=d>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let mut attr1 = HtmlTagAttribute::new();
+        attr1.add_char('a', AttributeField::Name);
+        attr1.add_char('b', AttributeField::Value);
+
+        let mut attr2 = HtmlTagAttribute::new();
+        attr2.add_char('c', AttributeField::Name);
+        attr2.add_char('d', AttributeField::Value);
+
+        let expected = [HtmlToken::StartTag {
+            tag: "p".to_string(),
+            self_closing: false,
+            attributes: vec![attr1, attr2],
+        }];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_null_in_tag_name() {
+        let html = "<a\0b></a\0b>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::StartTag {
+                tag: "a\u{FFFD}b".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::EndTag {
+                tag: "a\u{FFFD}b".to_string(),
+            },
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_null_in_attribute_name() {
+        let html = "<p a\0=x>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let mut attr = HtmlTagAttribute::new();
+        attr.add_char('a', AttributeField::Name);
+        attr.add_char('\u{FFFD}', AttributeField::Name);
+        attr.add_char('x', AttributeField::Value);
+
+        let expected = [HtmlToken::StartTag {
+            tag: "p".to_string(),
+            self_closing: false,
+            attributes: vec![attr],
+        }];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_attribute_value_keeps_case() {
+        // 属性名は小文字にするが、値は URL などの大文字小文字を区別するものがあるのでそのまま残す
+        let html = "<a HREF=\"/MixedCase\" Title='AbC' DATA-X=YyY>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let attributes = match tokenizer.next() {
+            Some(HtmlToken::StartTag { attributes, .. }) => attributes,
+            t => panic!("expected a start tag but got {:?}", t),
+        };
+
+        let pairs: Vec<(String, String)> = attributes.iter().map(|a| (a.name(), a.value())).collect();
+        assert_eq!(
+            vec![
+                ("href".to_string(), "/MixedCase".to_string()),
+                ("title".to_string(), "AbC".to_string()),
+                ("data-x".to_string(), "YyY".to_string()),
+            ],
+            pairs
+        );
+    }
+
+    #[test]
+    fn test_text_without_tag() {
+        let html = "abc".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::Char('a'),
+            HtmlToken::Char('b'),
+            HtmlToken::Char('c'),
+            HtmlToken::Eof,
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_start_tag_followed_by_eof() {
+        let html = "<body>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::StartTag {
+                tag: "body".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::Eof,
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_eof_in_tag_name() {
+        let html = "<bo".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_eof_in_character_reference() {
+        let html = "&am".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::Char('&'),
+            HtmlToken::Char('a'),
+            HtmlToken::Char('m'),
+            HtmlToken::Eof,
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_title_tag() {
+        let html = "<title>a<b></title>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::StartTag {
+                tag: "title".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::Char('a'),
+            HtmlToken::Char('<'),
+            HtmlToken::Char('b'),
+            HtmlToken::Char('>'),
+            HtmlToken::EndTag {
+                tag: "title".to_string(),
+            },
+            HtmlToken::Eof,
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_script_tag_with_end_tag_like_text() {
+        let html = "<script>a </ b</p>&amp;<c</script>".to_string();
+        let tokenizer = HtmlTokenizer::new(html);
+        let mut expected = vec![HtmlToken::StartTag {
+            tag: "script".to_string(),
+            self_closing: false,
+            attributes: Vec::new(),
+        }];
+        // script の中では </ も閉じるべきでない終了タグも文字参照も、そのまま文字として出てくる
+        expected.extend("a </ b</p>&amp;<c".chars().map(HtmlToken::Char));
+        expected.push(HtmlToken::EndTag {
+            tag: "script".to_string(),
+        });
+        expected.push(HtmlToken::Eof);
+
+        assert_eq!(expected, tokenizer.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_script_tag_with_partial_end_tag() {
+        // 閉じかけの </scr で入力が終わっても、止まらずに読んだ文字を全部出力する
+        let html = "<script>if (a</scr".to_string();
+        let tokenizer = HtmlTokenizer::new(html);
+        let mut expected = vec![HtmlToken::StartTag {
+            tag: "script".to_string(),
+            self_closing: false,
+            attributes: Vec::new(),
+        }];
+        expected.extend("if (a</scr".chars().map(HtmlToken::Char));
+        expected.push(HtmlToken::Eof);
+
+        assert_eq!(expected, tokenizer.collect::<Vec<_>>());
+
+        // 途中で別の文字が来た場合も、</scr をそのまま出力してから続きを読む
+        let html = "<script></scr</script>".to_string();
+        let tokenizer = HtmlTokenizer::new(html);
+        let mut expected = vec![HtmlToken::StartTag {
+            tag: "script".to_string(),
+            self_closing: false,
+            attributes: Vec::new(),
+        }];
+        expected.extend("</scr".chars().map(HtmlToken::Char));
+        expected.push(HtmlToken::EndTag {
+            tag: "script".to_string(),
+        });
+        expected.push(HtmlToken::Eof);
+
+        assert_eq!(expected, tokenizer.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_rcdata_end_tag_with_attributes() {
+        for (html, tag, text) in [
+            ("<title>t</title x>", "title", "t"),
+            ("<title>t</title/>", "title", "t"),
+            ("<textarea>x</textarea class=a>", "textarea", "x"),
+        ] {
+            let tokenizer = HtmlTokenizer::new(html.to_string());
+            let mut expected = vec![HtmlToken::StartTag {
+                tag: tag.to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            }];
+            expected.extend(text.chars().map(HtmlToken::Char));
+            // 終了タグの属性や / は読み捨てられる
+            expected.push(HtmlToken::EndTag {
+                tag: tag.to_string(),
+            });
+            expected.push(HtmlToken::Eof);
+
+            assert_eq!(expected, tokenizer.collect::<Vec<_>>(), "{}", html);
+        }
+    }
+
+    #[test]
+    fn test_script_end_tag_with_attributes() {
+        for html in ["<script>x</script foo>", "<script>x</script/>"] {
+            let tokenizer = HtmlTokenizer::new(html.to_string());
+            let expected = vec![
+                HtmlToken::StartTag {
+                    tag: "script".to_string(),
+                    self_closing: false,
+                    attributes: Vec::new(),
+                },
+                HtmlToken::Char('x'),
+                HtmlToken::EndTag {
+                    tag: "script".to_string(),
+                },
+                HtmlToken::Eof,
+            ];
+
+            assert_eq!(expected, tokenizer.collect::<Vec<_>>(), "{}", html);
+        }
+    }
+
+    #[test]
+    fn test_textarea_tag_with_end_tag_like_text() {
+        let html = "<textarea>x</b>&amp;</textarea>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::StartTag {
+                tag: "textarea".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::Char('x'),
+            HtmlToken::Char('<'),
+            HtmlToken::Char('/'),
+            HtmlToken::Char('b'),
+            HtmlToken::Char('>'),
+            HtmlToken::Char('&'),
+            HtmlToken::EndTag {
+                tag: "textarea".to_string(),
+            },
+            HtmlToken::Eof,
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert!(tokenizer.next().is_none());
+    }
+
+    #[test]
+    fn test_large_input() {
+        // 数 KB の入力でも、同じ断片を 1 つだけ与えたときのトークンの繰り返しになっていること
+        // バイト位置で読み進めているので、マルチバイト文字も混ぜておく
+        let fragment = "<p class=\"a\">日本語&amp;x</p>";
+        let fragment_tokens: Vec<HtmlToken> = HtmlTokenizer::new(fragment.to_string())
+            .filter(|t| *t != HtmlToken::Eof)
+            .collect();
+
+        let count = 500;
+        let html = fragment.repeat(count);
+        assert!(html.len() > 8 * 1024);
+
+        let mut expected = Vec::new();
+        for _ in 0..count {
+            expected.extend(fragment_tokens.iter().cloned());
+        }
+        expected.push(HtmlToken::Eof);
+
+        let tokens: Vec<HtmlToken> = HtmlTokenizer::new(html).collect();
+        assert_eq!(expected, tokens);
+    }
+
+    fn attribute(name: &str, value: &str) -> HtmlTagAttribute {
+        let mut attr = HtmlTagAttribute::new();
+        for c in name.chars() {
+            attr.add_char(c, AttributeField::Name);
+        }
+        for c in value.chars() {
+            attr.add_char(c, AttributeField::Value);
+        }
+        attr
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let html = "<body></body>".to_string();
+        let expected = vec![
+            HtmlToken::StartTag {
+                tag: "body".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::EndTag {
+                tag: "body".to_string(),
+            },
+        ];
+        assert_eq!(expected, HtmlTokenizer::new(html).tokenize());
+
+        assert!(HtmlTokenizer::new("".to_string()).tokenize().is_empty());
+    }
+
+    #[test]
+    fn test_truncated_attribute_value() {
+        for html in ["<a href=\"/x", "<a href='/x", "<a href=/x"] {
+            let mut tokenizer = HtmlTokenizer::new(html.to_string());
+            let expected = [
+                HtmlToken::StartTag {
+                    tag: "a".to_string(),
+                    self_closing: false,
+                    attributes: vec![attribute("href", "/x")],
+                },
+                HtmlToken::Eof,
+            ];
+            for e in expected {
+                assert_eq!(Some(e), tokenizer.next());
+            }
+            assert!(tokenizer.next().is_none());
+            assert_eq!(1, tokenizer.errors().len());
+        }
+    }
+
+    #[test]
+    fn test_character_reference_in_attribute_value() {
+        let cases = [
+            ("<a href=\"/a&amp;b\">", "/a&b"),
+            ("<a href='/a&amp;b'>", "/a&b"),
+            ("<a href=/a&amp;b>", "/a&b"),
+            ("<a title=\"&#65;&#x42;&lt\">", "AB&lt"),
+            ("<a title=\"a&b &unknown; c\">", "a&b &unknown; c"),
+        ];
+        for (html, value) in cases {
+            let mut tokenizer = HtmlTokenizer::new(html.to_string());
+            let name = if html.contains("href") { "href" } else { "title" };
+            let expected = HtmlToken::StartTag {
+                tag: "a".to_string(),
+                self_closing: false,
+                attributes: vec![attribute(name, value)],
+            };
+            assert_eq!(Some(expected), tokenizer.next(), "{}", html);
+            assert_eq!(Some(HtmlToken::Eof), tokenizer.next());
+        }
+    }
+
+    #[test]
+    fn test_eof_in_tag_error() {
+        let html = "<p>x</p>\n<a href=\"".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        let expected = [
+            HtmlToken::StartTag {
+                tag: "p".to_string(),
+                self_closing: false,
+                attributes: Vec::new(),
+            },
+            HtmlToken::Char('x'),
+            HtmlToken::EndTag {
+                tag: "p".to_string(),
+            },
+            HtmlToken::Char('\n'),
+            // 値が空のまま切れた属性も、作りかけの a タグとして出力される
+            HtmlToken::StartTag {
+                tag: "a".to_string(),
+                self_closing: false,
+                attributes: vec![attribute("href", "")],
+            },
+            HtmlToken::Eof,
+        ];
+        for e in expected {
+            assert_eq!(Some(e), tokenizer.next());
+        }
+        assert!(tokenizer.next().is_none());
+
+        // EOF は 2 行目の最後の文字 (") の位置として報告される
+        let errors = tokenizer.errors();
+        assert_eq!(1, errors.len());
+        assert_eq!("eof-in-tag", errors[0].message);
+        assert_eq!(2, errors[0].line);
+        assert_eq!(9, errors[0].column);
+        assert_eq!("eof-in-tag at line 2, column 9", alloc::format!("{}", errors[0]));
+    }
+
+    #[test]
+    fn test_no_error() {
+        let html = "<p class=\"a\">x</p>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        while tokenizer.next().is_some() {}
+        assert!(tokenizer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_state_after_script_start_tag() {
+        let html = "<script>a</script>".to_string();
+        let mut tokenizer = HtmlTokenizer::new(html);
+        assert_eq!(&TokenizerState::Data, tokenizer.state());
+
+        tokenizer.next();
+        assert_eq!(&TokenizerState::ScriptData, tokenizer.state());
+
+        // 終了タグを出したら Data に戻る
+        tokenizer.next();
+        tokenizer.next();
+        assert_eq!(&TokenizerState::Data, tokenizer.state());
+    }
 }
@@ -0,0 +1,4 @@
+pub mod style;
+pub mod render_tree;
+pub mod box_layout;
+pub mod paint;
@@ -0,0 +1,169 @@
+use core::cell::RefCell;
+
+use alloc::{rc::Rc, vec::Vec};
+
+use crate::renderer::{
+    dom::node::{Node, NodeKind},
+    layout::{
+        render_tree::{RenderNode, RenderTree},
+        style::ComputedStyle,
+    },
+};
+
+// テキストは折り返しを考えずに 1 行として扱い、この高さにする
+pub const LINE_HEIGHT: i64 = 20;
+// フォントの情報はないので、等幅フォントを仮定して 1 文字をこの幅にする
+pub const CHAR_WIDTH: i64 = 8;
+
+// [] 9.4.1 Block formatting contexts | CSS 2.2
+// https://www.w3.org/TR/CSS22/visuren.html#block-formatting
+// ----- Cited From Reference -----
+// In a block formatting context, boxes are laid out one after the other, vertically, beginning at the top of a containing block.
+// --------------------------------
+// margin や padding はまだ扱わないので、コンテンツの領域がそのまま箱の大きさになる
+#[derive(Debug, Clone)]
+pub struct LayoutBox {
+    pub node: Rc<RefCell<Node>>,
+    pub style: ComputedStyle,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    pub children: Vec<LayoutBox>,
+}
+
+// render tree を viewport_width の幅に並べる
+pub fn layout(render_tree: &RenderTree, viewport_width: i64) -> Option<LayoutBox> {
+    render_tree.root.as_ref().map(|root| layout_box(root, 0, 0, viewport_width))
+}
+
+// 子を上から順に縦に積み、その高さの合計を自分の高さにする
+// inline の要素も今のところ block と同じように積んでしまう
+// [] 9.4.2 Inline formatting contexts | CSS 2.2
+// https://www.w3.org/TR/CSS22/visuren.html#inline-formatting
+// ----- Cited From Reference -----
+// In an inline formatting context, boxes are laid out horizontally, one after the other, beginning at the top of a containing block.
+// --------------------------------
+// ただし隣り合ったテキストノードは 1 つの inline run として横に並べ、幅に収まらなくなったら次の行に送る
+fn layout_box(render_node: &RenderNode, x: i64, y: i64, width: i64) -> LayoutBox {
+    let mut children = Vec::new();
+    let mut height = 0;
+    // 今の行で次のテキストを置く位置 (x からのずれ)。直前の子がテキストでなければ None
+    let mut cursor: Option<i64> = None;
+
+    for child in &render_node.children {
+        if let NodeKind::Text(ref text) = child.node.borrow().kind {
+            let text_width = text.chars().count() as i64 * CHAR_WIDTH;
+            let offset = match cursor {
+                Some(c) if c == 0 || c + text_width <= width => c,
+                Some(_) => {
+                    height += LINE_HEIGHT;
+                    0
+                }
+                None => 0,
+            };
+            children.push(LayoutBox {
+                node: Rc::clone(&child.node),
+                style: child.style.clone(),
+                x: x + offset,
+                y: y + height,
+                width: text_width,
+                height: LINE_HEIGHT,
+                children: Vec::new(),
+            });
+            cursor = Some(offset + text_width);
+            continue;
+        }
+
+        // テキストの run が終わったので、その行の分だけ下に進める
+        if cursor.take().is_some() {
+            height += LINE_HEIGHT;
+        }
+
+        let child_box = layout_box(child, x, y + height, width);
+        height += child_box.height;
+        children.push(child_box);
+    }
+
+    if cursor.is_some() {
+        height += LINE_HEIGHT;
+    }
+
+    LayoutBox { node: Rc::clone(&render_node.node), style: render_node.style.clone(), x, y, width, height, children }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::{cssom::CssParser, token::CssTokenizer},
+        dom::{
+            api::get_elements_by_tag_name,
+            node::{append_child, ElementKind},
+        },
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+    };
+    use alloc::string::ToString;
+
+    // パーサは隣り合った文字を 1 つのテキストノードにまとめてしまうので、p の中にテキストノードを後から足して layout する
+    fn layout_with_texts(texts: &[&str], viewport_width: i64) -> LayoutBox {
+        let html = "<html><head></head><body><p></p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        for text in texts {
+            append_child(p, &Rc::new(RefCell::new(Node::new(NodeKind::Text(text.to_string())))));
+        }
+        let stylesheet = CssParser::new(CssTokenizer::new("".to_string())).parse_stylesheet();
+        let tree = RenderTree::new(&document, &stylesheet);
+
+        layout(&tree, viewport_width).expect("layout should have a root")
+    }
+
+    #[test]
+    fn test_block_layout() {
+        let html = "<html><head></head><body><p>a</p><p>b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("".to_string())).parse_stylesheet();
+        let tree = RenderTree::new(&document, &stylesheet);
+
+        let root = layout(&tree, 600).expect("layout should have a root");
+        assert_eq!((0, 0, 600, 2 * LINE_HEIGHT), (root.x, root.y, root.width, root.height));
+
+        let body = &root.children[0];
+        assert_eq!(Some(ElementKind::Body), body.node.borrow().get_element_kind());
+        assert_eq!(2, body.children.len());
+
+        let p1 = &body.children[0];
+        let p2 = &body.children[1];
+        assert_eq!((0, 0, 600, LINE_HEIGHT), (p1.x, p1.y, p1.width, p1.height));
+        assert_eq!((0, LINE_HEIGHT, 600, LINE_HEIGHT), (p2.x, p2.y, p2.width, p2.height));
+        assert!(p1.y < p2.y);
+    }
+
+    #[test]
+    fn test_adjacent_texts_on_one_line() {
+        let root = layout_with_texts(&["a", "b"], 600);
+        let p = &root.children[0].children[0];
+        assert_eq!(Some(ElementKind::P), p.node.borrow().get_element_kind());
+        assert_eq!((0, 0, 600, LINE_HEIGHT), (p.x, p.y, p.width, p.height));
+
+        assert_eq!(2, p.children.len());
+        let a = &p.children[0];
+        let b = &p.children[1];
+        assert_eq!((0, 0, CHAR_WIDTH, LINE_HEIGHT), (a.x, a.y, a.width, a.height));
+        assert_eq!((CHAR_WIDTH, 0, CHAR_WIDTH, LINE_HEIGHT), (b.x, b.y, b.width, b.height));
+    }
+
+    #[test]
+    fn test_adjacent_texts_wrap() {
+        // 3 文字分の幅には "ab" と "cd" は並ばないので、"cd" は次の行に送られる
+        let root = layout_with_texts(&["ab", "cd", "e"], 3 * CHAR_WIDTH);
+        let p = &root.children[0].children[0];
+        assert_eq!(2 * LINE_HEIGHT, p.height);
+
+        let positions: Vec<(i64, i64)> = p.children.iter().map(|c| (c.x, c.y)).collect();
+        assert_eq!(alloc::vec![(0, 0), (0, LINE_HEIGHT), (2 * CHAR_WIDTH, LINE_HEIGHT)], positions);
+    }
+}
@@ -0,0 +1,209 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::renderer::{
+    css::color::Color,
+    dom::node::NodeKind,
+    layout::box_layout::{LayoutBox, CHAR_WIDTH, LINE_HEIGHT},
+};
+
+// render_to_string で 1 文字として扱う大きさ (px)。layout が仮定している等幅フォントの 1 文字
+pub const CELL_WIDTH: i64 = CHAR_WIDTH;
+pub const CELL_HEIGHT: i64 = LINE_HEIGHT;
+
+// [] Appendix E. Elaborate description of Stacking Contexts | CSS 2.2
+// https://www.w3.org/TR/CSS22/zindex.html#painting-order
+// 描画する順に並べた、描くものの一覧。プラットフォーム側はこれを前から順に描けばよい
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayItem {
+    // 塗りつぶした長方形。今のところ要素の背景だけ
+    Rect { x: i64, y: i64, width: i64, height: i64, color: Color },
+    // (x, y) を左上にして 1 行で描く文字列
+    Text { text: String, x: i64, y: i64, color: Color },
+}
+
+// ----- Cited From Reference -----
+// 1. If the element is a root element: background color of element over the entire canvas.
+// 4. For all its in-flow, non-positioned, block-level descendants in tree order: If the element is a block, list-item, or other block equivalent: background color of element.
+// 7. Otherwise: first for the element, then for all its in-flow, non-positioned, block-level descendants in tree order: For each line box: text
+// --------------------------------
+// 背景を先に、その上に子孫を描く。本当は背景をすべて描いてからテキストを描くが、box が重ならないのでツリー順で十分
+pub fn paint(layout_box: &LayoutBox) -> Vec<DisplayItem> {
+    let mut items = Vec::new();
+    paint_box(layout_box, &mut items);
+    items
+}
+
+fn paint_box(layout_box: &LayoutBox, items: &mut Vec<DisplayItem>) {
+    match layout_box.node.borrow().kind {
+        NodeKind::Element(_) => {
+            if let Some(color) = layout_box.style.background_color {
+                items.push(DisplayItem::Rect {
+                    x: layout_box.x,
+                    y: layout_box.y,
+                    width: layout_box.width,
+                    height: layout_box.height,
+                    color,
+                });
+            }
+        }
+        NodeKind::Text(ref text) => items.push(DisplayItem::Text {
+            text: text.clone(),
+            x: layout_box.x,
+            y: layout_box.y,
+            color: layout_box.style.color,
+        }),
+        NodeKind::Document => {}
+    }
+
+    for child in &layout_box.children {
+        paint_box(child, items);
+    }
+}
+
+// テスト用に、display list を文字の格子に描く。width と height は px で、CELL_WIDTH x CELL_HEIGHT ごとに 1 文字になる
+// 何もないところは '.'、Rect は '#'、Text はその文字。色は無視する
+// 格子からはみ出した部分は捨てる
+pub fn render_to_string(items: &[DisplayItem], width: i64, height: i64) -> String {
+    let columns = (width / CELL_WIDTH).max(0) as usize;
+    let rows = (height / CELL_HEIGHT).max(0) as usize;
+    let mut grid = vec![vec!['.'; columns]; rows];
+
+    let mut put = |column: i64, row: i64, c: char| {
+        if 0 <= row && (row as usize) < rows && 0 <= column && (column as usize) < columns {
+            grid[row as usize][column as usize] = c;
+        }
+    };
+
+    for item in items {
+        match item {
+            DisplayItem::Rect { x, y, width, height, .. } => {
+                // 少しでもかかっているセルを塗る
+                for row in y.div_euclid(CELL_HEIGHT)..(y + height + CELL_HEIGHT - 1).div_euclid(CELL_HEIGHT) {
+                    for column in x.div_euclid(CELL_WIDTH)..(x + width + CELL_WIDTH - 1).div_euclid(CELL_WIDTH) {
+                        put(column, row, '#');
+                    }
+                }
+            }
+            DisplayItem::Text { text, x, y, .. } => {
+                let row = y.div_euclid(CELL_HEIGHT);
+                for (i, c) in text.chars().enumerate() {
+                    put(x.div_euclid(CELL_WIDTH) + i as i64, row, c);
+                }
+            }
+        }
+    }
+
+    let mut result = String::new();
+    for (i, row) in grid.iter().enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        result.extend(row.iter());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::{cssom::CssParser, token::CssTokenizer},
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+        layout::{
+            box_layout::{layout, LINE_HEIGHT},
+            render_tree::RenderTree,
+        },
+    };
+    use alloc::string::ToString;
+
+    fn paint_html(html: &str, style: &str, viewport_width: i64) -> Vec<DisplayItem> {
+        let window = HtmlParser::new(HtmlTokenizer::new(html.to_string())).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+        let tree = RenderTree::new(&document, &stylesheet);
+        let root = layout(&tree, viewport_width).expect("layout should have a root");
+        paint(&root)
+    }
+
+    #[test]
+    fn test_paint_single_block() {
+        let items = paint_html(
+            "<html><head></head><body><p>a</p></body></html>",
+            "p { color: red; background-color: blue; }",
+            600,
+        );
+
+        assert_eq!(
+            items,
+            vec![
+                DisplayItem::Rect { x: 0, y: 0, width: 600, height: LINE_HEIGHT, color: Color::new(0, 0, 255) },
+                DisplayItem::Text { text: "a".to_string(), x: 0, y: 0, color: Color::new(255, 0, 0) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paint_hex_background_color() {
+        let items = paint_html(
+            "<html><head></head><body><div><p>a</p></div></body></html>",
+            "p { background-color: #ff0000 }",
+            600,
+        );
+
+        // div には背景がないので、矩形は p の分だけ
+        assert_eq!(
+            items,
+            vec![
+                DisplayItem::Rect { x: 0, y: 0, width: 600, height: LINE_HEIGHT, color: Color::new(255, 0, 0) },
+                DisplayItem::Text { text: "a".to_string(), x: 0, y: 0, color: Color::black() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paint() {
+        let items = paint_html(
+            "<html><head></head><body><p>a</p><p class=\"x\">b</p></body></html>",
+            ".x { color: red; background-color: blue; }",
+            600,
+        );
+
+        assert_eq!(
+            items,
+            vec![
+                DisplayItem::Text { text: "a".to_string(), x: 0, y: 0, color: Color::black() },
+                DisplayItem::Rect { x: 0, y: LINE_HEIGHT, width: 600, height: LINE_HEIGHT, color: Color::new(0, 0, 255) },
+                DisplayItem::Text { text: "b".to_string(), x: 0, y: LINE_HEIGHT, color: Color::new(255, 0, 0) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paint_nothing() {
+        let items = paint_html("<html><head></head><body></body></html>", "", 600);
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn test_render_to_string() {
+        let items = paint_html(
+            "<html><head></head><body><p class=\"x\">ab</p><p>c</p></body></html>",
+            ".x { background-color: blue; }",
+            4 * CELL_WIDTH,
+        );
+
+        let expected = "ab##\nc...\n....";
+        assert_eq!(expected, render_to_string(&items, 4 * CELL_WIDTH, 3 * CELL_HEIGHT));
+    }
+
+    #[test]
+    fn test_render_to_string_clip() {
+        let items = [
+            DisplayItem::Rect { x: -CELL_WIDTH, y: 0, width: 2 * CELL_WIDTH, height: 1, color: Color::black() },
+            DisplayItem::Text { text: "hello".to_string(), x: CELL_WIDTH, y: CELL_HEIGHT, color: Color::black() },
+        ];
+
+        assert_eq!("#..\n.he", render_to_string(&items, 3 * CELL_WIDTH, 2 * CELL_HEIGHT));
+        assert_eq!("", render_to_string(&items, 0, 0));
+    }
+}
@@ -0,0 +1,144 @@
+use core::cell::RefCell;
+
+use alloc::{rc::Rc, vec::Vec};
+
+use crate::renderer::{
+    css::cssom::StyleSheet,
+    dom::node::{ElementKind, Node, NodeKind},
+    layout::style::{ComputedStyle, Display, StyleContext},
+};
+
+// [] 2. The CSS Box Model, Rendering | CSS Display Module Level 3
+// https://www.w3.org/TR/css-display-3/#box-generation
+// DOM のうち実際に描画されるノードだけを残したもの。各ノードは ComputedStyle を持つ
+// Text は親の要素の ComputedStyle をそのまま持つ
+#[derive(Debug, Clone)]
+pub struct RenderNode {
+    pub node: Rc<RefCell<Node>>,
+    pub style: ComputedStyle,
+    pub children: Vec<RenderNode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderTree {
+    pub root: Option<RenderNode>,
+}
+
+impl RenderTree {
+    pub fn new(document: &Rc<RefCell<Node>>, stylesheet: &StyleSheet) -> Self {
+        let styles = StyleContext::new(document, stylesheet);
+
+        // Document 自体は描画されないので、最初に描画される子を根にする
+        let mut root = None;
+        let mut child = document.borrow().first_child();
+        while let Some(c) = child {
+            root = build_render_node(&c, &styles, None);
+            if root.is_some() {
+                break;
+            }
+            child = c.borrow().next_sibling();
+        }
+
+        Self { root }
+    }
+}
+
+// node 以下の部分木を RenderNode にする。node 自体が描画されないなら部分木ごと捨てる
+fn build_render_node(
+    node: &Rc<RefCell<Node>>,
+    styles: &StyleContext,
+    parent_style: Option<&ComputedStyle>,
+) -> Option<RenderNode> {
+    let style = match node.borrow().kind {
+        NodeKind::Element(ref e) => {
+            // head と script と style の中身は表示しない
+            if matches!(e.kind(), ElementKind::Head | ElementKind::Script | ElementKind::Style) {
+                return None;
+            }
+
+            let style = styles.computed_style(node)?.clone();
+            if style.display == Display::None {
+                return None;
+            }
+            style
+        }
+        NodeKind::Text(_) => parent_style?.clone(),
+        NodeKind::Document => return None,
+    };
+
+    let mut children = Vec::new();
+    let mut child = node.borrow().first_child();
+    while let Some(c) = child {
+        if let Some(render_node) = build_render_node(&c, styles, Some(&style)) {
+            children.push(render_node);
+        }
+        child = c.borrow().next_sibling();
+    }
+
+    Some(RenderNode { node: Rc::clone(node), style, children })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        css::{cssom::CssParser, token::CssTokenizer},
+        dom::api::{get_element_by_id, get_elements_by_tag_name},
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+    };
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_render_tree() {
+        let html = "<html><head><style>.hidden { display: none; }</style></head><body><p>a</p><div class=\"hidden\"><p>b</p></div><div>c</div></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new(".hidden { display: none; }".to_string())).parse_stylesheet();
+
+        let tree = RenderTree::new(&document, &stylesheet);
+        let root = tree.root.expect("render tree should have a root");
+        assert_eq!(Some(ElementKind::Html), root.node.borrow().get_element_kind());
+
+        // head は落ちて body だけが残る
+        assert_eq!(1, root.children.len());
+        let body = &root.children[0];
+        assert_eq!(Some(ElementKind::Body), body.node.borrow().get_element_kind());
+
+        // display: none の div は中身ごと落ちる
+        assert_eq!(2, body.children.len());
+        assert_eq!(Some(ElementKind::P), body.children[0].node.borrow().get_element_kind());
+        assert_eq!(Some(ElementKind::Div), body.children[1].node.borrow().get_element_kind());
+        assert_eq!("a", body.children[0].node.borrow().text_content());
+        assert_eq!("c", body.children[1].node.borrow().text_content());
+
+        // Text は残り、親のスタイルを持つ
+        let text = &body.children[0].children[0];
+        assert_eq!(NodeKind::Text("a".to_string()), text.node.borrow().kind);
+        assert_eq!(body.children[0].style, text.style);
+    }
+
+    fn contains(render_node: &RenderNode, node: &Rc<RefCell<Node>>) -> bool {
+        Rc::ptr_eq(&render_node.node, node) || render_node.children.iter().any(|c| contains(c, node))
+    }
+
+    #[test]
+    fn test_display_none_prunes_subtree() {
+        let html = "<html><head></head><body><div id=\"hidden\"><section><p>x</p></section></div><p>y</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        // 子孫に display: block を指定しても、祖先が display: none なら描画されない
+        let style = "#hidden { display: none; } p { display: block; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let tree = RenderTree::new(&document, &stylesheet);
+        let root = tree.root.expect("render tree should have a root");
+
+        let hidden = get_element_by_id(&document, "hidden").expect("failed to find #hidden");
+        let ps = get_elements_by_tag_name(&document, "p");
+        assert_eq!(2, ps.len());
+        assert!(!contains(&root, &hidden));
+        assert!(!contains(&root, &ps[0]));
+        assert!(!contains(&root, &ps[0].borrow().first_child().expect("p should have a text")));
+        assert!(contains(&root, &ps[1]));
+    }
+}
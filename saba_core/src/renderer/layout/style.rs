@@ -0,0 +1,702 @@
+use core::cell::RefCell;
+
+use alloc::{collections::BTreeMap, rc::Rc, string::ToString, vec::Vec};
+
+use crate::renderer::{
+    css::{
+        color::Color,
+        cssom::{CssParser, Declaration, StyleSheet},
+        token::{CssToken, CssTokenizer},
+    },
+    dom::{
+        api::selector_matches,
+        node::{dom_iter, ElementKind, Node},
+    },
+};
+
+// [] 9.2.4 The display property | CSS 2.2
+// https://www.w3.org/TR/CSS22/visuren.html#display-prop
+// とりあえず block と inline と none だけ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Display {
+    Block,
+    Inline,
+    None,
+}
+
+impl Display {
+    // 本当は UA stylesheet (default_stylesheet) で決まるものだが、cascade を通さずに使えるよう要素の種類からも決め打ちしておく
+    pub fn default_for(kind: &ElementKind) -> Self {
+        match kind {
+            ElementKind::Head | ElementKind::Title | ElementKind::Style | ElementKind::Script => Display::None,
+            ElementKind::Html
+            | ElementKind::Body
+            | ElementKind::P
+            | ElementKind::Div
+            | ElementKind::Ul
+            | ElementKind::Ol
+            | ElementKind::Li
+            | ElementKind::H1
+            | ElementKind::H2
+            | ElementKind::H3
+            | ElementKind::H4
+            | ElementKind::H5
+            | ElementKind::H6 => Display::Block,
+            _ => Display::Inline,
+        }
+    }
+
+    fn from_css(token: &CssToken) -> Option<Self> {
+        match token {
+            CssToken::Ident(ident) => match ident.as_str() {
+                "block" => Some(Display::Block),
+                "inline" => Some(Display::Inline),
+                "none" => Some(Display::None),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+// [] 8.3 Margin properties | CSS 2.2
+// https://www.w3.org/TR/CSS22/box.html#margin-properties
+// margin と padding の上下左右の値。単位は px だけ扱う
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EdgeSizes {
+    pub top: i64,
+    pub right: i64,
+    pub bottom: i64,
+    pub left: i64,
+}
+
+impl EdgeSizes {
+    // [] 8.3 Margin properties | CSS 2.2
+    // https://www.w3.org/TR/CSS22/box.html#propdef-margin
+    // ----- Cited From Reference -----
+    // If there is only one component value, it applies to all sides. If there are two values, the top and bottom margins are set to the first value and the right and left margins are set to the second. If there are three values, the top is set to the first value, the left and right are set to the second, and the bottom is set to the third. If there are four values, they apply to the top, right, bottom, and left, respectively.
+    // --------------------------------
+    // padding の shorthand も同じ規則
+    fn from_css(values: &[CssToken]) -> Option<Self> {
+        let mut lengths = Vec::new();
+        for value in values {
+            lengths.push(length_from_css(value)?);
+        }
+
+        match lengths[..] {
+            [all] => Some(Self { top: all, right: all, bottom: all, left: all }),
+            [vertical, horizontal] => Some(Self { top: vertical, right: horizontal, bottom: vertical, left: horizontal }),
+            [top, horizontal, bottom] => Some(Self { top, right: horizontal, bottom, left: horizontal }),
+            [top, right, bottom, left] => Some(Self { top, right, bottom, left }),
+            _ => None,
+        }
+    }
+
+    // margin-top などの longhand。知らない辺の名前なら何もしない
+    fn set(&mut self, side: &str, value: i64) {
+        match side {
+            "top" => self.top = value,
+            "right" => self.right = value,
+            "bottom" => self.bottom = value,
+            "left" => self.left = value,
+            _ => {}
+        }
+    }
+}
+
+// font-size の初期値 medium を px にしたもの
+const INITIAL_FONT_SIZE: f64 = 16.0;
+
+// [] 15.7 Font size: the 'font-size' property | CSS 2.2
+// https://www.w3.org/TR/CSS22/fonts.html#font-size-props
+// ----- Cited From Reference -----
+// Percentages: refer to inherited font size
+// --------------------------------
+// em も親の font-size が基準になる。知らない単位やキーワードは None にして、継承した値をそのまま使わせる
+fn font_size_from_css(token: &CssToken, parent_font_size: f64) -> Option<f64> {
+    match token {
+        CssToken::Dimension(n, unit) if unit.eq_ignore_ascii_case("px") => Some(*n),
+        CssToken::Dimension(n, unit) if unit.eq_ignore_ascii_case("em") => Some(*n * parent_font_size),
+        CssToken::Percentage(n) => Some(*n / 100.0 * parent_font_size),
+        _ => None,
+    }
+}
+
+// 単位のない数値も px として扱う (本当は 0 以外は無効)
+fn length_from_css(token: &CssToken) -> Option<i64> {
+    match token {
+        CssToken::Number(n) => Some(*n as i64),
+        CssToken::Dimension(n, unit) if unit.eq_ignore_ascii_case("px") => Some(*n as i64),
+        _ => None,
+    }
+}
+
+// [] 6.1 Cascaded values | CSS Cascading and Inheritance Level 4
+// https://www.w3.org/TR/css-cascade-4/#computed
+// 要素ごとにカスケードを解決した結果。プロパティはサポートしているものだけ持つ
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedStyle {
+    pub color: Color,
+    // 指定がなければ透明なので None
+    pub background_color: Option<Color>,
+    pub display: Display,
+    pub margin: EdgeSizes,
+    pub padding: EdgeSizes,
+    // px
+    pub font_size: f64,
+}
+
+impl ComputedStyle {
+    // [] 7.1 Initial Values | CSS Cascading and Inheritance Level 4
+    // https://www.w3.org/TR/css-cascade-4/#initial-values
+    // ----- Cited From Reference -----
+    // Each property has an initial value, defined in the property's definition table.
+    // --------------------------------
+    // どのルールもマッチしないときの値。color は黒、margin と padding は 0、font-size は medium (16px)
+    // display の初期値は本当は inline だが、UA stylesheet の代わりに要素の種類で block / none にしている
+    pub fn initial(kind: &ElementKind) -> Self {
+        Self {
+            color: Color::black(),
+            background_color: None,
+            display: Display::default_for(kind),
+            margin: EdgeSizes::default(),
+            padding: EdgeSizes::default(),
+            font_size: INITIAL_FONT_SIZE,
+        }
+    }
+
+    // [] 7.2 Inherited Values | CSS Cascading and Inheritance Level 4
+    // https://www.w3.org/TR/css-cascade-4/#inheriting
+    // 継承するプロパティ (color, font-size) は親の値を、継承しないプロパティ (background-color, display, margin, padding) は初期値を使う
+    pub fn inherit(kind: &ElementKind, parent: &ComputedStyle) -> Self {
+        Self { color: parent.color, font_size: parent.font_size, ..Self::initial(kind) }
+    }
+
+    // 知らないプロパティや解釈できない値は無視する
+    // parent_font_size は em や % の font-size を解決するのに使う
+    fn apply(&mut self, declaration: &Declaration, parent_font_size: f64) {
+        let value = match declaration.value() {
+            Some(v) => v,
+            None => return,
+        };
+
+        match declaration.property.as_str() {
+            "color" => {
+                if let Some(color) = Color::from_css(value) {
+                    self.color = color;
+                }
+            }
+            "background-color" => {
+                if let Some(color) = Color::from_css(value) {
+                    self.background_color = Some(color);
+                }
+            }
+            "display" => {
+                if let Some(display) = Display::from_css(value) {
+                    self.display = display;
+                }
+            }
+            "font-size" => {
+                if let Some(font_size) = font_size_from_css(value, parent_font_size) {
+                    self.font_size = font_size;
+                }
+            }
+            "margin" => {
+                if let Some(margin) = EdgeSizes::from_css(&declaration.values) {
+                    self.margin = margin;
+                }
+            }
+            "padding" => {
+                if let Some(padding) = EdgeSizes::from_css(&declaration.values) {
+                    self.padding = padding;
+                }
+            }
+            property => {
+                if let Some(side) = property.strip_prefix("margin-") {
+                    if let Some(length) = length_from_css(value) {
+                        self.margin.set(side, length);
+                    }
+                } else if let Some(side) = property.strip_prefix("padding-") {
+                    if let Some(length) = length_from_css(value) {
+                        self.padding.set(side, length);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// [] 6.2. Cascading Origins | CSS Cascading and Inheritance Level 4
+// https://www.w3.org/TR/css-cascade-4/#cascading-origins
+// user origin はないので UA と author だけ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    UserAgent,
+    Author,
+}
+
+impl Origin {
+    // [] 6.1. Cascade Sorting Order | CSS Cascading and Inheritance Level 4
+    // https://www.w3.org/TR/css-cascade-4/#cascade-origin
+    // ----- Cited From Reference -----
+    // Declarations from origins earlier in this list win over declarations from later origins.
+    // 1. Transition declarations 2. Important user agent declarations 3. Important user declarations 4. Important author declarations 5. Animation declarations 6. Normal author declarations 7. Normal user declarations 8. Normal user agent declarations
+    // --------------------------------
+    // 大きいほど優先される。!important がつくと origin の順番がひっくり返る
+    fn precedence(&self, important: bool) -> u8 {
+        match (self, important) {
+            (Origin::UserAgent, false) => 0,
+            (Origin::Author, false) => 1,
+            (Origin::Author, true) => 2,
+            (Origin::UserAgent, true) => 3,
+        }
+    }
+}
+
+// [] Appendix D. Default style sheet for HTML 4 | CSS 2.2
+// https://www.w3.org/TR/CSS22/sample.html
+// 本物の UA stylesheet のうち、サポートしているプロパティに関係するところだけ
+const DEFAULT_STYLESHEET: &str = "
+    html, body, div, p, ul, ol, li, h1, h2, h3, h4, h5, h6 { display: block; }
+    head, title, style, script { display: none; }
+";
+
+// 作者の stylesheet より前に適用する UA stylesheet
+pub fn default_stylesheet() -> StyleSheet {
+    CssParser::new(CssTokenizer::new(DEFAULT_STYLESHEET.to_string())).parse_stylesheet()
+}
+
+// [] 6. Cascading | CSS Cascading and Inheritance Level 4
+// https://www.w3.org/TR/css-cascade-4/#cascading
+// ----- Cited From Reference -----
+// The cascade sorts declarations according to the following criteria, in descending order of priority:
+// Origin and Importance ... Specificity ... Order of Appearance
+// --------------------------------
+// dom 以下の要素ごとに、マッチするルールの宣言を優先度の低い順に並べて上書きしていく
+// stylesheet は作者のもので、UA stylesheet (default_stylesheet) はこの中で勝手に足す
+// 返り値はドキュメント順に並んでいる
+pub fn cascade(dom: &Rc<RefCell<Node>>, stylesheet: &StyleSheet) -> Vec<(Rc<RefCell<Node>>, ComputedStyle)> {
+    let mut styles = Vec::new();
+    // 祖先の ComputedStyle を引くための、ノードのアドレスから styles の添字への対応
+    let mut indices = BTreeMap::new();
+    let user_agent = default_stylesheet();
+    let stylesheets = [(Origin::UserAgent, &user_agent), (Origin::Author, stylesheet)];
+
+    for node in dom_iter(dom) {
+        let kind = match node.borrow().get_element_kind() {
+            Some(k) => k,
+            None => continue,
+        };
+
+        // [] 6.1. Cascade Sorting Order | CSS Cascading and Inheritance Level 4
+        // https://www.w3.org/TR/css-cascade-4/#style-attr
+        // ----- Cited From Reference -----
+        // Element-Attached Styles: Separately for normal and important declarations, declarations that are attached directly to an element (such as the contents of a style attribute) rather than indirectly mapped by means of a style rule selector take precedence over declarations the same importance that are mapped via style rule.
+        // --------------------------------
+        // style 属性は author origin で、selector のついたルールより優先される。読めない declaration は無視する
+        let inline_declarations = match node.borrow().get_element().and_then(|e| e.get_attribute("style")) {
+            Some(style) => CssParser::new(CssTokenizer::new(style)).parse_declarations(),
+            None => Vec::new(),
+        };
+
+        // (origin と important, style 属性か, specificity, 出現順) でソートすれば後ろほど優先度が高くなる
+        let mut declarations = Vec::new();
+        for (origin, sheet) in stylesheets {
+            for (order, rule) in sheet.rules.iter().enumerate() {
+                let specificity = match rule
+                    .selectors
+                    .iter()
+                    .filter(|selector| selector_matches(selector, &node))
+                    .map(|selector| selector.specificity())
+                    .max()
+                {
+                    Some(s) => s,
+                    None => continue,
+                };
+
+                for declaration in &rule.declarations {
+                    declarations.push(((origin.precedence(declaration.important), false, specificity, order), declaration));
+                }
+            }
+        }
+        for (order, declaration) in inline_declarations.iter().enumerate() {
+            declarations.push(((Origin::Author.precedence(declaration.important), true, (0, 0, 0), order), declaration));
+        }
+        declarations.sort_by_key(|(key, _)| *key);
+
+        let mut style = match parent_style(&styles, &indices, &node) {
+            Some(parent) => ComputedStyle::inherit(&kind, parent),
+            None => ComputedStyle::initial(&kind),
+        };
+        let parent_font_size = style.font_size;
+        for (_, declaration) in declarations {
+            style.apply(declaration, parent_font_size);
+        }
+        indices.insert(Rc::as_ptr(&node), styles.len());
+        styles.push((node, style));
+    }
+
+    styles
+}
+
+// cascade の結果をノードから引けるようにしたもの。ノードのアドレス (Rc::as_ptr) をキーにする
+// ノードの Rc も一緒に持っておき、ノードが解放されてアドレスが別のノードに使い回されないようにする
+#[derive(Debug, Clone)]
+pub struct StyleContext {
+    styles: BTreeMap<*const RefCell<Node>, (Rc<RefCell<Node>>, ComputedStyle)>,
+}
+
+impl StyleContext {
+    pub fn new(dom: &Rc<RefCell<Node>>, stylesheet: &StyleSheet) -> Self {
+        let styles = cascade(dom, stylesheet)
+            .into_iter()
+            .map(|(node, style)| (Rc::as_ptr(&node), (node, style)))
+            .collect();
+        Self { styles }
+    }
+
+    // node の ComputedStyle。要素でないノードや、cascade した木の外のノードなら None
+    pub fn computed_style(&self, node: &Rc<RefCell<Node>>) -> Option<&ComputedStyle> {
+        self.styles.get(&Rc::as_ptr(node)).map(|(_, style)| style)
+    }
+}
+
+// いちばん近い祖先の要素の ComputedStyle を探す。ドキュメント順に処理しているので祖先の分はもう計算済み
+// 親が Document のときは None になる
+// ノードは dom に持たれているので、cascade の間はアドレスが別のノードに使い回されることはない
+fn parent_style<'a>(
+    styles: &'a [(Rc<RefCell<Node>>, ComputedStyle)],
+    indices: &BTreeMap<*const RefCell<Node>, usize>,
+    node: &Rc<RefCell<Node>>,
+) -> Option<&'a ComputedStyle> {
+    let mut ancestor = node.borrow().parent().upgrade();
+    while let Some(a) = ancestor {
+        if let Some(index) = indices.get(&Rc::as_ptr(&a)) {
+            return Some(&styles[*index].1);
+        }
+        ancestor = a.borrow().parent().upgrade();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{
+        dom::{
+            api::{get_element_by_id, get_elements_by_tag_name},
+            node::NodeKind,
+        },
+        html::{parser::HtmlParser, token::HtmlTokenizer},
+    };
+
+    fn computed_style_of(styles: &[(Rc<RefCell<Node>>, ComputedStyle)], node: &Rc<RefCell<Node>>) -> ComputedStyle {
+        styles
+            .iter()
+            .find(|(n, _)| Rc::ptr_eq(n, node))
+            .map(|(_, style)| style.clone())
+            .expect("failed to find a computed style of the node")
+    }
+
+    #[test]
+    fn test_initial() {
+        let html = "<html><head></head><body><p>a</p><a>b</a></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("div { color: red; }".to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(ComputedStyle::initial(&ElementKind::P), computed_style_of(&styles, p));
+        assert_eq!(Color::black(), computed_style_of(&styles, p).color);
+        assert_eq!(Display::Block, computed_style_of(&styles, p).display);
+
+        let a = &get_elements_by_tag_name(&document, "a")[0];
+        assert_eq!(Display::Inline, computed_style_of(&styles, a).display);
+    }
+
+    #[test]
+    fn test_default_stylesheet() {
+        let html = "<html><head><title>t</title></head><body><h1>a</h1></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+
+        // 作者の stylesheet がなくても UA stylesheet が効く
+        let styles = cascade(&document, &StyleSheet::new());
+        let head = &get_elements_by_tag_name(&document, "head")[0];
+        assert_eq!(Display::None, computed_style_of(&styles, head).display);
+        let h1 = &get_elements_by_tag_name(&document, "h1")[0];
+        assert_eq!(Display::Block, computed_style_of(&styles, h1).display);
+
+        // UA stylesheet 単体でも head を消すルールを持っている
+        let user_agent = default_stylesheet();
+        assert!(user_agent
+            .rules
+            .iter()
+            .filter(|rule| rule.selectors.iter().any(|selector| selector_matches(selector, head)))
+            .flat_map(|rule| rule.declarations.iter())
+            .any(|declaration| declaration.property == "display"
+                && declaration.value() == Some(&CssToken::Ident("none".to_string()))));
+    }
+
+    #[test]
+    fn test_author_overrides_default_stylesheet() {
+        let html = "<html><head></head><body><h1>a</h1></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "head { display: block; } h1 { display: inline; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let head = &get_elements_by_tag_name(&document, "head")[0];
+        assert_eq!(Display::Block, computed_style_of(&styles, head).display);
+        let h1 = &get_elements_by_tag_name(&document, "h1")[0];
+        assert_eq!(Display::Inline, computed_style_of(&styles, h1).display);
+    }
+
+    #[test]
+    fn test_cascade() {
+        let html = "<html><head></head><body><p>a</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("p { color: red; }".to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(computed_style_of(&styles, p).color, Color::new(255, 0, 0));
+        assert_eq!(computed_style_of(&styles, p).display, Display::Block);
+
+        let body = &get_elements_by_tag_name(&document, "body")[0];
+        assert_eq!(computed_style_of(&styles, body).color, Color::black());
+    }
+
+    #[test]
+    fn test_style_context() {
+        let html = "<html><head></head><body><p>a</p><p id=\"x\">b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("#x { color: red; }".to_string())).parse_stylesheet();
+
+        let context = StyleContext::new(&document, &stylesheet);
+        let x = get_element_by_id(&document, "x").expect("p#x should exist");
+        assert_eq!(Some(Color::new(255, 0, 0)), context.computed_style(&x).map(|s| s.color));
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(Some(Color::black()), context.computed_style(p).map(|s| s.color));
+
+        // テキストノードや、別の木のノードには ComputedStyle はない
+        let text = p.borrow().first_child().expect("text should exist");
+        assert!(context.computed_style(&text).is_none());
+        assert!(context.computed_style(&Rc::new(RefCell::new(Node::new(NodeKind::Document)))).is_none());
+    }
+
+    #[test]
+    fn test_compound_selector_does_not_overmatch() {
+        let html = "<html><head></head><body><p>a</p><p class=\"w\">b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("p.w { display: none; }".to_string())).parse_stylesheet();
+
+        // p.w はまだ読めないので、どちらの p にもマッチしない
+        let styles = cascade(&document, &stylesheet);
+        for p in get_elements_by_tag_name(&document, "p") {
+            assert_eq!(Display::Block, computed_style_of(&styles, &p).display);
+        }
+    }
+
+    #[test]
+    fn test_cascade_order() {
+        let html = "<html><head></head><body><p id=\"x\" class=\"y z\">a</p><p class=\"z\">b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "#x { color: green; } .z { color: blue; } p { color: red; display: inline; } p { display: none; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+
+        // id が class より、class が type より優先される。specificity が同じなら後ろの宣言が勝つ
+        let x = get_element_by_id(&document, "x").expect("failed to find #x");
+        assert_eq!(computed_style_of(&styles, &x).color, Color::new(0, 128, 0));
+        assert_eq!(computed_style_of(&styles, &x).display, Display::None);
+        let second = &get_elements_by_tag_name(&document, "p")[1];
+        assert_eq!(computed_style_of(&styles, second).color, Color::new(0, 0, 255));
+    }
+
+    #[test]
+    fn test_cascade_important() {
+        let html = "<html><head></head><body><p id=\"x\">a</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "p { color: red !important; } #x { color: green; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let x = get_element_by_id(&document, "x").expect("failed to find #x");
+        assert_eq!(computed_style_of(&styles, &x).color, Color::new(255, 0, 0));
+    }
+
+    #[test]
+    fn test_inline_style() {
+        let html = "<html><head></head><body><p id=\"x\" style=\"color: green\">a</p><p style=\"color: ; display: inline\">b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "#x { color: red; } p { color: red; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+
+        // style 属性は id セレクタよりも優先される
+        let x = get_element_by_id(&document, "x").expect("failed to find #x");
+        assert_eq!(computed_style_of(&styles, &x).color, Color::new(0, 128, 0));
+
+        // 解釈できない declaration は無視されて、残りは効く
+        let second = &get_elements_by_tag_name(&document, "p")[1];
+        assert_eq!(computed_style_of(&styles, second).color, Color::new(255, 0, 0));
+        assert_eq!(computed_style_of(&styles, second).display, Display::Inline);
+    }
+
+    #[test]
+    fn test_inline_style_with_unknown_chars() {
+        let html = "<html><head></head><body><p style=\"font: 12px/1.5 serif; color: green\">a</p><p style=\"font-family: 日本語; a: $; color: blue\">b</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("".to_string())).parse_stylesheet();
+
+        // 知らない文字を含む declaration があっても cascade は止まらず、残りの declaration は効く
+        let styles = cascade(&document, &stylesheet);
+        let p = get_elements_by_tag_name(&document, "p");
+        assert_eq!(computed_style_of(&styles, &p[0]).color, Color::new(0, 128, 0));
+        assert_eq!(computed_style_of(&styles, &p[1]).color, Color::new(0, 0, 255));
+    }
+
+    #[test]
+    fn test_important_beats_inline_style() {
+        let html = "<html><head></head><body><p style=\"color: green\">a</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("p { color: red !important; }".to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(computed_style_of(&styles, p).color, Color::new(255, 0, 0));
+    }
+
+    #[test]
+    fn test_font_size_px() {
+        let html = "<html><head></head><body><p>a</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("p { font-size: 16px; }".to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(computed_style_of(&styles, p).font_size, 16.0);
+    }
+
+    #[test]
+    fn test_font_size_relative() {
+        let html = "<html><head></head><body><div><p>a</p><span>b</span><a>c</a></div></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "div { font-size: 10px; } p { font-size: 2em; font-size: 2em; } span { font-size: 150%; } a { font-size: 3vw; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        // 同じ要素に em が何回あっても、基準は親の font-size
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(computed_style_of(&styles, p).font_size, 20.0);
+        let span = &get_elements_by_tag_name(&document, "span")[0];
+        assert_eq!(computed_style_of(&styles, span).font_size, 15.0);
+        // 知らない単位なら親の値を継承する
+        let a = &get_elements_by_tag_name(&document, "a")[0];
+        assert_eq!(computed_style_of(&styles, a).font_size, 10.0);
+
+        let body = &get_elements_by_tag_name(&document, "body")[0];
+        assert_eq!(computed_style_of(&styles, body).font_size, 16.0);
+    }
+
+    #[test]
+    fn test_inherit() {
+        let html = "<html><head></head><body><div><p>a</p></div></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "body { color: blue; display: inline; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+
+        // color は継承されるが display は継承されない
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(computed_style_of(&styles, p).color, Color::new(0, 0, 255));
+        assert_eq!(computed_style_of(&styles, p).display, Display::Block);
+        let html = &get_elements_by_tag_name(&document, "html")[0];
+        assert_eq!(computed_style_of(&styles, html).color, Color::black());
+    }
+
+    #[test]
+    fn test_background_color_not_inherited() {
+        let html = "<html><head></head><body><div><p>a</p></div></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let style = "div { background-color: #ff0000; } p { background-color: nothing; }";
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+
+        let div = &get_elements_by_tag_name(&document, "div")[0];
+        assert_eq!(Some(Color::new(255, 0, 0)), computed_style_of(&styles, div).background_color);
+        // 継承しないので、子は初期値 (transparent) のまま。解釈できない値も無視される
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(None, computed_style_of(&styles, p).background_color);
+    }
+
+    fn margin_of(style: &str) -> EdgeSizes {
+        let html = "<html><head></head><body><p>a</p></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new(style.to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        computed_style_of(&styles, p).margin
+    }
+
+    #[test]
+    fn test_margin_one_value() {
+        assert_eq!(EdgeSizes { top: 10, right: 10, bottom: 10, left: 10 }, margin_of("p { margin: 10; }"));
+    }
+
+    #[test]
+    fn test_margin_two_values() {
+        assert_eq!(EdgeSizes { top: 10, right: 20, bottom: 10, left: 20 }, margin_of("p { margin: 10px 20px; }"));
+    }
+
+    #[test]
+    fn test_margin_four_values() {
+        assert_eq!(EdgeSizes { top: 1, right: 2, bottom: 3, left: 4 }, margin_of("p { margin: 1 2 3 4; }"));
+    }
+
+    #[test]
+    fn test_margin_longhand_and_invalid() {
+        // longhand は shorthand の後に書けばその辺だけ上書きする
+        assert_eq!(
+            EdgeSizes { top: 10, right: 10, bottom: 10, left: 5 },
+            margin_of("p { margin: 10; margin-left: 5px; }")
+        );
+        // 解釈できない値が混じっていれば宣言ごと無視する
+        assert_eq!(EdgeSizes::default(), margin_of("p { margin: 10 auto; }"));
+    }
+
+    #[test]
+    fn test_padding_not_inherited() {
+        let html = "<html><head></head><body><div><p>a</p></div></body></html>".to_string();
+        let window = HtmlParser::new(HtmlTokenizer::new(html)).construct_tree();
+        let document = window.borrow().document();
+        let stylesheet = CssParser::new(CssTokenizer::new("div { padding: 1 2 3; }".to_string())).parse_stylesheet();
+
+        let styles = cascade(&document, &stylesheet);
+        let div = &get_elements_by_tag_name(&document, "div")[0];
+        assert_eq!(EdgeSizes { top: 1, right: 2, bottom: 3, left: 2 }, computed_style_of(&styles, div).padding);
+        let p = &get_elements_by_tag_name(&document, "p")[0];
+        assert_eq!(EdgeSizes::default(), computed_style_of(&styles, p).padding);
+    }
+}
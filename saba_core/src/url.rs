@@ -1,5 +1,7 @@
 use alloc::string::{String, ToString};
 
+use crate::error::Error;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Url {
     url: String,
@@ -19,13 +21,13 @@ impl Url {
         }
     }
 
-    pub fn parse(&self) -> Result<Self, String> {
+    pub fn parse(&self) -> Result<Self, Error> {
         if self.is_not_http() {
-            return Err(String::from("Only HTTP scheme is supported."))
+            return Err(Error::Url(String::from("Only HTTP scheme is supported.")))
         }
 
         let Some(host) = self.extract_host() else {
-            return Err(String::from("Host parse failed"))
+            return Err(Error::Url(String::from("Host parse failed")))
         };
         let port = self.extract_port();
         let path = self.extract_path();
@@ -34,6 +36,30 @@ impl Url {
         Ok(Url { url: self.url.clone(), host, port, path, searchpart })
     }
 
+    pub fn host(&self) -> String {
+        self.host.clone()
+    }
+
+    pub fn port(&self) -> String {
+        self.port.clone()
+    }
+
+    // TCP の接続に使えるように数値にしたもの。数値として読めない port は URL として不正とみなす
+    pub fn port_number(&self) -> Result<u16, Error> {
+        self.port
+            .parse::<u16>()
+            .map_err(|_| Error::Url(alloc::format!("Invalid port number: {}", self.port)))
+    }
+
+    // 先頭の / は含まない
+    pub fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    pub fn searchpart(&self) -> String {
+        self.searchpart.clone()
+    }
+
     // host が取れない場合だけは URL として不正とみなしたいので Option 型を返す
     fn extract_host(&self) -> Option<String> {
         self.url
@@ -86,92 +112,108 @@ mod tests {
     #[test]
     fn test_url() {
         let url = "http://example.com".to_string();
-        let expected = Ok(Url {
+        let expected = Url {
             url: url.clone(),
             host: "example.com".to_string(),
             port: "80".to_string(),
             path: "".to_string(),
             searchpart: "".to_string(),
-        });
-        assert_eq!(expected, Url::new(&url).parse());
+        };
+        assert_eq!(expected, Url::new(&url).parse().expect("failed to parse a url"));
     }
 
     #[test]
     fn test_url_with_port() {
         let url = "http://example.com:8888".to_string();
-        let expected = Ok(Url {
+        let expected = Url {
             url: url.clone(),
             host: "example.com".to_string(),
             port: "8888".to_string(),
             path: "".to_string(),
             searchpart: "".to_string(),
-        });
-        assert_eq!(expected, Url::new(&url).parse());
+        };
+        assert_eq!(expected, Url::new(&url).parse().expect("failed to parse a url"));
     }
 
     #[test]
     fn test_url_with_path() {
         let url = "http://example.com/index.html".to_string();
-        let expected = Ok(Url {
+        let expected = Url {
             url: url.clone(),
             host: "example.com".to_string(),
             port: "80".to_string(),
             path: "index.html".to_string(),
             searchpart: "".to_string(),
-        });
-        assert_eq!(expected, Url::new(&url).parse());
+        };
+        assert_eq!(expected, Url::new(&url).parse().expect("failed to parse a url"));
     }
 
     #[test]
     fn test_url_with_port_path() {
         let url = "http://example.com:8888/index.html".to_string();
-        let expected = Ok(Url {
+        let expected = Url {
             url: url.clone(),
             host: "example.com".to_string(),
             port: "8888".to_string(),
             path: "index.html".to_string(),
             searchpart: "".to_string(),
-        });
-        assert_eq!(expected, Url::new(&url).parse());
+        };
+        assert_eq!(expected, Url::new(&url).parse().expect("failed to parse a url"));
     }
 
     #[test]
     fn test_url_with_port_path_searchpart() {
         let url = "http://example.com:8888/index.html?a=123&b=456".to_string();
-        let expected = Ok(Url {
+        let expected = Url {
             url: url.clone(),
             host: "example.com".to_string(),
             port: "8888".to_string(),
             path: "index.html".to_string(),
             searchpart: "a=123&b=456".to_string(),
-        });
-        assert_eq!(expected, Url::new(&url).parse());
+        };
+        assert_eq!(expected, Url::new(&url).parse().expect("failed to parse a url"));
     }
 
     #[test]
     fn test_localhost() {
         let url = "http://localhost:8000".to_string();
-        let expected = Ok(Url {
+        let expected = Url {
             url: url.clone(),
             host: "localhost".to_string(),
             port: "8000".to_string(),
             path: "".to_string(),
             searchpart: "".to_string(),
-        });
-        assert_eq!(expected, Url::new(&url).parse());
+        };
+        assert_eq!(expected, Url::new(&url).parse().expect("failed to parse a url"));
     }
 
     #[test]
     fn test_no_scheme() {
         let url = "example.com".to_string();
-        let expected = Err("Only HTTP scheme is supported.".to_string());
+        let expected = Err(Error::Url("Only HTTP scheme is supported.".to_string()));
         assert_eq!(expected, Url::new(&url).parse());
     }
 
     #[test]
     fn test_unsupported_scheme() {
         let url = "https://example.com:8888/index.html".to_string();
-        let expected = Err("Only HTTP scheme is supported.".to_string());
+        let expected = Err(Error::Url("Only HTTP scheme is supported.".to_string()));
         assert_eq!(expected, Url::new(&url).parse());
     }
+
+    #[test]
+    fn test_getters() {
+        let url = Url::new("http://example.com:8888/index.html?a=123").parse().expect("failed to parse a url");
+        assert_eq!("example.com", url.host());
+        assert_eq!("8888", url.port());
+        assert_eq!(Ok(8888), url.port_number());
+        assert_eq!("index.html", url.path());
+        assert_eq!("a=123", url.searchpart());
+    }
+
+    #[test]
+    fn test_invalid_port_number() {
+        let url = Url::new("http://example.com:99999/").parse().expect("failed to parse a url");
+        assert_eq!(Err(Error::Url("Invalid port number: 99999".to_string())), url.port_number());
+    }
 }
@@ -1,20 +1,59 @@
 #![no_std]
 #![cfg_attr(not(target_os = "linux"), no_main)]
 extern crate alloc;
-use alloc::string::ToString;
+use alloc::string::String;
 use net_wasabi::http::HttpClient;
 use noli::prelude::*;
+use saba_core::http::HttpResponse;
+use saba_core::renderer::html::{parser::HtmlParser, token::HtmlTokenizer};
+use saba_core::url::Url;
+
+// 今のところ取りに行くページは固定。そのうち引数で受け取れるようにしたい
+const TARGET_URL: &str = "http://example.net/";
 
 fn main() {
+    let url = match Url::new(TARGET_URL).parse() {
+        Ok(url) => url,
+        Err(e) => {
+            print!("{}", e);
+            return;
+        }
+    };
+    let port = match url.port_number() {
+        Ok(port) => port,
+        Err(e) => {
+            print!("{}", e);
+            return;
+        }
+    };
+
     let client = HttpClient::new();
-    match client.get("example.net".to_string(), 80, "/".to_string()) {
+    match client.get(url.host(), port, url.path()) {
         Ok(res) => {
-            print!("response: \n {:#?}", res);
+            print!("{}", render_as_text(&res));
         }
         Err(e) => {
-            print!("error: \n {:#?}", e);
+            print!("{}", e);
         }
     }
 }
 
+// HTML ならパースして DOM のテキストだけを取り出す。それ以外の Content-Type なら本文をそのまま返す
+// Content-Type がなければ HTML だとみなす
+fn render_as_text(res: &HttpResponse) -> String {
+    // header の名前も media type も大文字小文字を区別しない
+    let content_type = res
+        .header_value_ignore_case("Content-Type")
+        .unwrap_or_else(|_| "text/html".into())
+        .to_ascii_lowercase();
+    if !content_type.contains("text/html") {
+        return res.body();
+    }
+
+    let window = HtmlParser::new(HtmlTokenizer::new(res.body())).construct_tree();
+    let document = window.borrow().document();
+    let text = document.borrow().text_content();
+    text
+}
+
 entry_point!(main);